@@ -1,28 +1,28 @@
 use nalgebra::dimension::*;
 use toy_quant::{
-    gates::{binary::gates::cnot, unitary::gates::h},
+    circuit::Circuit,
     qubit::Qubit,
-    registers::quantum::QuantumRegister,
+    registers::{
+        classical::ClassicalRegister, quantum::QuantumRegister,
+    },
 };
 
 fn entangle_qubits(
     ket_a: Qubit,
     ket_b: Qubit,
 ) -> QuantumRegister<U4> {
-    let ket_a = h().run(ket_a);
     let merged = QuantumRegister::from_2_qubits(ket_a, ket_b);
-    cnot().apply(merged)
+    Circuit::new(2).h(0).cnot(0, 1).run(merged)
 }
 
+#[allow(clippy::println_empty_string)]
 fn eval_qubits(ket_a: Qubit, ket_b: Qubit) {
     println!("∣{}{}⟩ becomes", ket_a.sample(), ket_b.sample());
-    let mut states = [0, 0, 0, 0];
     let reg = entangle_qubits(ket_a, ket_b);
-    for _ in 0..1000 {
-        states[reg.collapse().bits as usize] += 1;
-    }
+    let states = reg.sample_counts(1000);
     for (idx, val) in states.iter().enumerate() {
-        println!("∣{:02b}⟩ * {}", idx, *val as f32 / 1000.0)
+        let reg = ClassicalRegister::from(idx as u64);
+        println!("∣{:2}⟩ * {}", reg, *val as f32 / 1000.0)
     }
     println!("");
 }
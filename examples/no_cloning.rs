@@ -0,0 +1,33 @@
+use toy_quant::{
+    gates::binary::{best_cloning_fidelity, gates::cnot, BinaryGate},
+    qubit::Qubit,
+    Float,
+};
+
+fn main() {
+    let cnot = cnot();
+    let basis = [Qubit::zero(), Qubit::one()];
+    println!(
+        "CNOT copies |0⟩ and |1⟩ with fidelity {}",
+        best_cloning_fidelity(&cnot, &basis)
+    );
+    let zero_plus = [Qubit::zero(), Qubit::plus()];
+    println!(
+        "but |0⟩ and |+⟩ only with fidelity {}",
+        best_cloning_fidelity(&cnot, &zero_plus)
+    );
+
+    let mut rng = rand::thread_rng();
+    let best = (0..10000)
+        .map(|_| {
+            best_cloning_fidelity(
+                &BinaryGate::random(&mut rng),
+                &zero_plus,
+            )
+        })
+        .fold(0.0, Float::max);
+    println!(
+        "The best of 10000 random gates manages {}, never 1",
+        best
+    );
+}
@@ -0,0 +1,20 @@
+use toy_quant::{protocols::teleport, qubit::Qubit};
+
+fn main() {
+    let inputs = [
+        ("|0⟩", Qubit::zero()),
+        ("|1⟩", Qubit::one()),
+        ("|-⟩", Qubit::minus()),
+        ("θ=1, φ=2", Qubit::from_theta_phi(1.0, 2.0)),
+    ];
+    for (name, input) in &inputs {
+        let (z_bit, x_bit, output) = teleport(input);
+        println!(
+            "{}: measured {}{}, fidelity {}",
+            name,
+            z_bit as u8,
+            x_bit as u8,
+            output.fidelity(input)
+        );
+    }
+}
@@ -0,0 +1,1063 @@
+//! Sequences of gates on a register
+
+use crate::complex::Complex;
+use crate::gates::binary::{self, BinaryGate};
+use crate::gates::unitary::{self, UnaryGate};
+use crate::noise::Channel;
+use crate::registers::quantum::QuantumRegister;
+
+use approx::abs_diff_eq;
+use nalgebra::allocator::Allocator;
+use nalgebra::default_allocator::DefaultAllocator;
+use nalgebra::dimension::DimName;
+use nalgebra::DMatrix;
+use rand::Rng;
+
+use std::error::Error;
+use std::fmt;
+
+/// A gate applied to particular qubits of a register.
+///
+/// Qubit 0 is the most significant bit of the basis state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    /// A single qubit gate on `target`
+    Unary { gate: UnaryGate, target: usize },
+    /// A two qubit gate, where `first` is the gate's high-order qubit
+    Binary {
+        gate: BinaryGate,
+        first: usize,
+        second: usize,
+    },
+}
+
+impl Operation {
+    /// The qubits the operation acts on
+    pub fn qubits(&self) -> Vec<usize> {
+        match self {
+            Operation::Unary { target, .. } => vec![*target],
+            Operation::Binary { first, second, .. } => {
+                vec![*first, *second]
+            }
+        }
+    }
+
+    /// Whether running `next` straight after this operation does
+    /// nothing, as with two Hadamards on the same qubit
+    fn is_undone_by(&self, next: &Operation) -> bool {
+        match (self, next) {
+            (
+                Operation::Unary { gate, target },
+                Operation::Unary {
+                    gate: next,
+                    target: next_target,
+                },
+            ) => {
+                target == next_target
+                    && abs_diff_eq!(
+                        gate.dagger(),
+                        *next,
+                        epsilon = 1e-6
+                    )
+            }
+            (
+                Operation::Binary {
+                    gate,
+                    first,
+                    second,
+                },
+                Operation::Binary {
+                    gate: next,
+                    first: next_first,
+                    second: next_second,
+                },
+            ) => {
+                // The same gate with its qubits listed the other way
+                // round is the swapped gate
+                let next = if (first, second)
+                    == (next_first, next_second)
+                {
+                    next.clone()
+                } else if (first, second) == (next_second, next_first)
+                {
+                    next.swap()
+                } else {
+                    return false;
+                };
+                abs_diff_eq!(gate.dagger(), next, epsilon = 1e-6)
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn apply_to_slice(&self, amps: &mut [Complex]) {
+        match self {
+            Operation::Unary { gate, target } => {
+                gate.apply_to_slice(amps, *target)
+            }
+            Operation::Binary {
+                gate,
+                first,
+                second,
+            } => gate.apply_to_slice(amps, *first, *second),
+        }
+    }
+}
+
+/// A two qubit gate that OpenQASM has no name for, from
+/// [`Circuit::to_qasm`]. `index` is its position in
+/// [`ops`](Circuit::ops).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoQasmName {
+    pub index: usize,
+}
+
+impl fmt::Display for NoQasmName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Can't write operation {} as OpenQASM, since it's a two \
+             qubit gate other than CNOT, SWAP or CZ",
+            self.index
+        )
+    }
+}
+
+impl Error for NoQasmName {}
+
+/// A sequence of operations on a fixed number of qubits
+#[derive(Debug, Clone, PartialEq)]
+pub struct Circuit {
+    n_qubits: usize,
+    ops: Vec<Operation>,
+    noise: Option<Channel>,
+}
+
+impl Circuit {
+    /// An empty circuit on `n_qubits` qubits
+    pub fn new(n_qubits: usize) -> Self {
+        Self {
+            n_qubits,
+            ops: Vec::new(),
+            noise: None,
+        }
+    }
+
+    /// Follow every operation with a random event from `channel` on each
+    /// of the qubits it acted on, so [`run`](Self::run) simulates one
+    /// noisy trajectory.
+    pub fn with_noise(mut self, channel: Channel) -> Self {
+        self.noise = Some(channel);
+        self
+    }
+
+    /// Add an operation to the end of the circuit.
+    ///
+    /// Panics if it acts on a qubit the circuit doesn't have, or is a
+    /// two qubit gate given the same qubit twice.
+    pub fn push(mut self, op: Operation) -> Self {
+        for q in op.qubits() {
+            assert!(
+                q < self.n_qubits,
+                "Qubit {} out of range for a {} qubit circuit",
+                q,
+                self.n_qubits
+            );
+        }
+        if let Operation::Binary { first, second, .. } = op {
+            assert_ne!(
+                first, second,
+                "A two qubit gate needs two different qubits, not {} twice",
+                first
+            );
+        }
+        self.ops.push(op);
+        self
+    }
+
+    /// Add a single qubit gate on `target`
+    pub fn unary(self, gate: UnaryGate, target: usize) -> Self {
+        self.push(Operation::Unary { gate, target })
+    }
+
+    /// Add a two qubit gate, with `first` as its high-order qubit
+    pub fn binary(
+        self,
+        gate: BinaryGate,
+        first: usize,
+        second: usize,
+    ) -> Self {
+        self.push(Operation::Binary {
+            gate,
+            first,
+            second,
+        })
+    }
+
+    /// Add a Hadamard gate on `target`
+    pub fn h(self, target: usize) -> Self {
+        self.unary(unitary::gates::h(), target)
+    }
+
+    /// Add a Pauli X (NOT) gate on `target`
+    pub fn x(self, target: usize) -> Self {
+        self.unary(unitary::gates::pauli::x(), target)
+    }
+
+    /// Add a Pauli Y gate on `target`
+    pub fn y(self, target: usize) -> Self {
+        self.unary(unitary::gates::pauli::y(), target)
+    }
+
+    /// Add a Pauli Z gate on `target`
+    pub fn z(self, target: usize) -> Self {
+        self.unary(unitary::gates::pauli::z(), target)
+    }
+
+    /// Add an S gate on `target`
+    pub fn s(self, target: usize) -> Self {
+        self.unary(unitary::gates::s(), target)
+    }
+
+    /// Add a T gate on `target`
+    pub fn t(self, target: usize) -> Self {
+        self.unary(unitary::gates::t(), target)
+    }
+
+    /// Add a CNOT gate from `control` to `target`
+    pub fn cnot(self, control: usize, target: usize) -> Self {
+        self.binary(binary::gates::cnot(), control, target)
+    }
+
+    /// Add a controlled Z gate between `a` and `b`
+    pub fn cz(self, a: usize, b: usize) -> Self {
+        self.binary(binary::gates::cz(), a, b)
+    }
+
+    /// Add a gate swapping qubits `a` and `b`
+    pub fn swap(self, a: usize, b: usize) -> Self {
+        self.binary(binary::gates::swap(), a, b)
+    }
+
+    /// The same circuit without pairs of operations that undo each
+    /// other, such as `h(0).h(0)` or `cnot(0, 1).cnot(0, 1)`. Operations
+    /// on other qubits in between don't stop a pair cancelling, and
+    /// removing a pair can expose another, so `h(0).x(0).x(0).h(0)`
+    /// cancels completely.
+    ///
+    /// A circuit [`with_noise`](Self::with_noise) comes back unchanged,
+    /// since every operation there also brings a round of noise.
+    pub fn optimize(&self) -> Circuit {
+        if self.is_noisy() {
+            return self.clone();
+        }
+        let mut ops: Vec<Operation> = Vec::new();
+        for op in &self.ops {
+            let qubits = op.qubits();
+            // The last operation kept that shares a qubit with `op`
+            let prev = ops.iter().rposition(|prev| {
+                prev.qubits().iter().any(|q| qubits.contains(q))
+            });
+            match prev {
+                Some(i) if ops[i].is_undone_by(op) => {
+                    ops.remove(i);
+                }
+                _ => ops.push(op.clone()),
+            }
+        }
+        Circuit {
+            ops,
+            ..self.clone()
+        }
+    }
+
+    /// Add the gate called `name` on `qubits`, as named in
+    /// [`to_json`](Self::to_json) and on the command line: `h`, `x`,
+    /// `y`, `z`, `s` and `t` on one qubit, and `cnot`, `cz` and `swap`
+    /// on two.
+    ///
+    /// Fails, saying why, for an unknown gate or unsuitable qubits.
+    #[cfg(any(feature = "cli", feature = "serde"))]
+    pub(crate) fn push_named(
+        self,
+        name: &str,
+        qubits: &[usize],
+    ) -> Result<Self, String> {
+        let arity = match name {
+            "h" | "x" | "y" | "z" | "s" | "t" => 1,
+            "cnot" | "cz" | "swap" => 2,
+            _ => return Err(format!("unknown gate {:?}", name)),
+        };
+        check_qubits(name, arity, qubits, self.n_qubits)?;
+        let q = qubits;
+        Ok(match name {
+            "h" => self.h(q[0]),
+            "x" => self.x(q[0]),
+            "y" => self.y(q[0]),
+            "z" => self.z(q[0]),
+            "s" => self.s(q[0]),
+            "t" => self.t(q[0]),
+            "cnot" => self.cnot(q[0], q[1]),
+            "cz" => self.cz(q[0], q[1]),
+            _ => self.swap(q[0], q[1]),
+        })
+    }
+
+    /// The circuit as an [OpenQASM 2.0](https://arxiv.org/abs/1707.03429)
+    /// program. Single qubit gates without a QASM name are written as
+    /// `u3`, which drops their global phase.
+    ///
+    /// Fails if the circuit has a two qubit gate other than CNOT, SWAP
+    /// or CZ, since OpenQASM 2.0 has no way to write a general one.
+    pub fn to_qasm(&self) -> Result<String, NoQasmName> {
+        let mut qasm =
+            String::from("OPENQASM 2.0;\ninclude \"qelib1.inc\";\n");
+        qasm += &format!("qreg q[{}];\n", self.n_qubits);
+        qasm += &format!("creg c[{}];\n", self.n_qubits);
+        for (index, op) in self.ops.iter().enumerate() {
+            let line = match op {
+                Operation::Unary { gate, target } => {
+                    match gate.name() {
+                        Some(name) => {
+                            format!(
+                                "{} q[{}];",
+                                name.to_lowercase(),
+                                target
+                            )
+                        }
+                        None => {
+                            let (beta, gamma, delta, _) =
+                                gate.to_parameters();
+                            // Adding 0 turns -0 into 0
+                            format!(
+                                "u3({},{},{}) q[{}];",
+                                gamma + 0.0,
+                                beta + 0.0,
+                                delta + 0.0,
+                                target
+                            )
+                        }
+                    }
+                }
+                Operation::Binary {
+                    gate,
+                    first,
+                    second,
+                } => {
+                    let name = match gate.name() {
+                        Some("CNOT") => "cx",
+                        Some("SWAP") => "swap",
+                        Some("CZ") => "cz",
+                        _ => return Err(NoQasmName { index }),
+                    };
+                    format!("{} q[{}],q[{}];", name, first, second)
+                }
+            };
+            qasm += &line;
+            qasm.push('\n');
+        }
+        Ok(qasm)
+    }
+
+    /// The circuit as JSON, in the form
+    ///
+    /// ```json
+    /// {
+    ///   "qubits": 2,
+    ///   "ops": [
+    ///     {"gate": "h", "targets": [0]},
+    ///     {"gate": "cnot", "targets": [0, 1]}
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// The gates are `h`, `x`, `y`, `z`, `s`, `t`, `cnot`, `cz` and
+    /// `swap`. Any other single qubit gate is written as `u`, with
+    /// `"params"` `[β, γ, δ, α]` from [`UnaryGate::to_parameters`], and
+    /// any other two qubit gate as `matrix`, with a `"matrix"` of its 16
+    /// entries, row by row, each as `{"re": .., "im": ..}`. Noise isn't
+    /// included.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        let ops = self
+            .ops
+            .iter()
+            .map(|op| match op {
+                Operation::Unary { gate, target } => {
+                    let (name, params) = match gate.name() {
+                        Some(name) => (name.to_lowercase(), vec![]),
+                        None => {
+                            let (b, g, d, a) = gate.to_parameters();
+                            ("u".to_string(), vec![b, g, d, a])
+                        }
+                    };
+                    json::Op {
+                        gate: name,
+                        targets: vec![*target],
+                        params,
+                        matrix: Vec::new(),
+                    }
+                }
+                Operation::Binary {
+                    gate,
+                    first,
+                    second,
+                } => {
+                    let (name, matrix) = match gate.name() {
+                        Some(name) => (name.to_lowercase(), vec![]),
+                        // Transposed, since nalgebra iterates by column
+                        None => (
+                            "matrix".to_string(),
+                            gate.mat
+                                .transpose()
+                                .iter()
+                                .cloned()
+                                .collect(),
+                        ),
+                    };
+                    json::Op {
+                        gate: name,
+                        targets: vec![*first, *second],
+                        params: Vec::new(),
+                        matrix,
+                    }
+                }
+            })
+            .collect();
+        let circuit = json::Circuit {
+            qubits: self.n_qubits,
+            ops,
+        };
+        serde_json::to_string(&circuit)
+            .expect("Circuits always serialize")
+    }
+
+    /// Read a circuit written by [`to_json`](Self::to_json), failing
+    /// on malformed JSON, unknown gates, or bad targets.
+    #[cfg(feature = "serde")]
+    pub fn from_json(s: &str) -> Result<Circuit, serde_json::Error> {
+        use serde::de::Error;
+        let json: json::Circuit = serde_json::from_str(s)?;
+        let n_qubits = json.qubits;
+        let mut circuit = Circuit::new(n_qubits);
+        for op in json.ops {
+            let params = op.params.as_slice();
+            if op.gate != "matrix" && !op.matrix.is_empty() {
+                return Err(Error::custom(format!(
+                    "only matrix takes a matrix, not {}",
+                    op.gate
+                )));
+            }
+            circuit = if op.gate == "matrix" {
+                if op.matrix.len() != 16 || !params.is_empty() {
+                    return Err(Error::custom(
+                        "matrix needs 16 matrix entries and no parameters",
+                    ));
+                }
+                check_qubits("matrix", 2, &op.targets, n_qubits)
+                    .map_err(Error::custom)?;
+                let gate = BinaryGate::try_new(
+                    nalgebra::Matrix4::from_row_slice(&op.matrix),
+                )
+                .map_err(Error::custom)?;
+                circuit.binary(gate, op.targets[0], op.targets[1])
+            } else if op.gate == "u" {
+                if params.len() != 4 {
+                    return Err(Error::custom(format!(
+                        "u needs 4 parameters, not {:?}",
+                        params
+                    )));
+                }
+                check_qubits("u", 1, &op.targets, n_qubits)
+                    .map_err(Error::custom)?;
+                let gate = UnaryGate::from_parameters((
+                    params[0], params[1], params[2], params[3],
+                ));
+                circuit.unary(gate, op.targets[0])
+            } else {
+                if !params.is_empty() {
+                    return Err(Error::custom(format!(
+                        "{} takes no parameters, not {:?}",
+                        op.gate, params
+                    )));
+                }
+                circuit
+                    .push_named(&op.gate, &op.targets)
+                    .map_err(Error::custom)?
+            };
+        }
+        Ok(circuit)
+    }
+
+    /// A text diagram of the circuit, one wire per qubit and one column
+    /// per operation:
+    ///
+    /// ```text
+    /// q0: ─H──●─
+    ///         │
+    /// q1: ────⊕─
+    /// ```
+    pub fn draw(&self) -> String {
+        // Wires on the even rows, and the gaps between them on the odd
+        // ones
+        let n_rows = (2 * self.n_qubits).saturating_sub(1);
+        let labels: Vec<_> =
+            (0..self.n_qubits).map(|q| format!("q{}: ", q)).collect();
+        let indent =
+            labels.iter().map(|l| l.len()).max().unwrap_or(0);
+        let mut rows: Vec<String> = (0..n_rows)
+            .map(|row| {
+                if row % 2 == 0 {
+                    format!("{:>1$}", labels[row / 2], indent)
+                } else {
+                    " ".repeat(indent)
+                }
+            })
+            .collect();
+        for op in &self.ops {
+            // What each qubit shows in this column
+            let marks: Vec<(usize, String)> = match op {
+                Operation::Unary { gate, target } => {
+                    vec![(
+                        *target,
+                        gate.name().unwrap_or("U").to_string(),
+                    )]
+                }
+                Operation::Binary {
+                    gate,
+                    first,
+                    second,
+                } => {
+                    let (a, b) = match gate.name() {
+                        Some("CNOT") => ("●", "⊕"),
+                        Some("CZ") => ("●", "●"),
+                        Some("SWAP") => ("×", "×"),
+                        _ => ("U", "U"),
+                    };
+                    vec![
+                        (*first, a.to_string()),
+                        (*second, b.to_string()),
+                    ]
+                }
+            };
+            let width = marks
+                .iter()
+                .map(|(_, m)| m.chars().count())
+                .max()
+                .unwrap_or(1);
+            let qubits = op.qubits();
+            let low = *qubits.iter().min().unwrap();
+            let high = *qubits.iter().max().unwrap();
+            for (row, line) in rows.iter_mut().enumerate() {
+                let cell = if row % 2 == 1 {
+                    // Connect the wires a two qubit gate spans
+                    let spanned = low < row / 2 + 1 && row / 2 < high;
+                    let mid = if spanned { "│" } else { " " };
+                    format!(" {:^1$} ", mid, width)
+                } else if let Some((_, mark)) =
+                    marks.iter().find(|(q, _)| *q == row / 2)
+                {
+                    format!("─{:─^1$}─", mark, width)
+                } else if low < row / 2 && row / 2 < high {
+                    format!("─{:─^1$}─", "┼", width)
+                } else {
+                    "─".repeat(width + 2)
+                };
+                line.push_str(&cell);
+            }
+        }
+        let rows: Vec<_> =
+            rows.iter().map(|r| r.trim_end()).collect();
+        rows.join("\n")
+    }
+
+    pub fn n_qubits(&self) -> usize {
+        self.n_qubits
+    }
+
+    pub fn ops(&self) -> &[Operation] {
+        &self.ops
+    }
+
+    /// Run every operation in order on a register with `n_qubits` qubits
+    pub fn run<N: DimName>(
+        &self,
+        reg: QuantumRegister<N>,
+    ) -> QuantumRegister<N>
+    where
+        DefaultAllocator: Allocator<Complex, N>,
+    {
+        self.run_with_rng(reg, &mut rand::thread_rng())
+    }
+
+    /// [`run`](Self::run), drawing the noise from `rng`. Without noise
+    /// this is deterministic and never touches `rng`.
+    pub fn run_with_rng<N: DimName, R: Rng>(
+        &self,
+        reg: QuantumRegister<N>,
+        rng: &mut R,
+    ) -> QuantumRegister<N>
+    where
+        DefaultAllocator: Allocator<Complex, N>,
+    {
+        assert_eq!(
+            N::dim(),
+            1 << self.n_qubits,
+            "A {} qubit circuit needs a {} state register",
+            self.n_qubits,
+            1 << self.n_qubits
+        );
+        let mut qubits = reg.into_vector();
+        self.apply_to_slice(qubits.as_mut_slice(), rng);
+        QuantumRegister::from_vector_unchecked(qubits)
+    }
+
+    /// Run the circuit, and any noise, in place on the amplitudes of a
+    /// register, as [`run_with_rng`](Self::run_with_rng) does
+    pub(crate) fn apply_to_slice<R: Rng>(
+        &self,
+        amps: &mut [Complex],
+        rng: &mut R,
+    ) {
+        for op in &self.ops {
+            op.apply_to_slice(amps);
+            if let Some(channel) = &self.noise {
+                for q in op.qubits() {
+                    channel.apply_to_slice(amps, q, rng);
+                }
+            }
+        }
+    }
+
+    /// Whether the circuit has noise from
+    /// [`with_noise`](Self::with_noise), so each run can differ
+    pub(crate) fn is_noisy(&self) -> bool {
+        self.noise.is_some()
+    }
+}
+
+/// Check that `qubits` are `arity` different qubits of an `n_qubits`
+/// qubit circuit, for the gate called `name`
+#[cfg(any(feature = "cli", feature = "serde"))]
+fn check_qubits(
+    name: &str,
+    arity: usize,
+    qubits: &[usize],
+    n_qubits: usize,
+) -> Result<(), String> {
+    if qubits.len() != arity {
+        return Err(format!(
+            "{} acts on {} qubit(s), not {}",
+            name,
+            arity,
+            qubits.len()
+        ));
+    }
+    if let Some(q) = qubits.iter().find(|&&q| q >= n_qubits) {
+        return Err(format!(
+            "qubit {} out of range for a {} qubit circuit",
+            q, n_qubits
+        ));
+    }
+    if arity == 2 && qubits[0] == qubits[1] {
+        return Err(format!("{} needs two different qubits", name));
+    }
+    Ok(())
+}
+
+/// The JSON form of a circuit, for [`Circuit::to_json`]
+#[cfg(feature = "serde")]
+mod json {
+    use crate::complex::Complex;
+    use crate::Float;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct Circuit {
+        pub qubits: usize,
+        pub ops: Vec<Op>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct Op {
+        pub gate: String,
+        pub targets: Vec<usize>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub params: Vec<Float>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub matrix: Vec<Complex>,
+    }
+}
+
+/// The unitary matrix of running `ops` in order on `n_qubits` qubits.
+pub fn circuit_matrix(
+    ops: &[Operation],
+    n_qubits: usize,
+) -> DMatrix<Complex> {
+    let dim = 1 << n_qubits;
+    let mut mat = DMatrix::from_element(dim, dim, Complex::zero());
+    // Column `i` is where the circuit sends the basis state `i`
+    for i in 0..dim {
+        let mut amps = vec![Complex::zero(); dim];
+        amps[i] = Complex::one();
+        for op in ops {
+            op.apply_to_slice(&mut amps);
+        }
+        mat.column_mut(i).copy_from_slice(&amps);
+    }
+    mat
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts::FRAC_1_SQRT_2;
+    use crate::gates::binary::gates::cnot;
+    use crate::gates::unitary::gates::h;
+    use approx::assert_relative_eq;
+    use nalgebra::DVector;
+
+    fn bell_ops() -> Vec<Operation> {
+        vec![
+            Operation::Unary {
+                gate: h(),
+                target: 0,
+            },
+            Operation::Binary {
+                gate: cnot(),
+                first: 0,
+                second: 1,
+            },
+        ]
+    }
+
+    #[test]
+    fn bell_circuit_matrix() {
+        let mat = circuit_matrix(&bell_ops(), 2);
+        #[rustfmt::skip]
+        let expected = DMatrix::from_row_slice(4, 4, &[
+            1.0, 0.0, 1.0, 0.0,
+            0.0, 1.0, 0.0, 1.0,
+            0.0, 1.0, 0.0, -1.0,
+            1.0, 0.0, -1.0, 0.0,
+        ])
+        .map(|x| Complex::from_re(x * FRAC_1_SQRT_2));
+        assert_relative_eq!(mat, expected, epsilon = 1e-6);
+
+        let ket_00 = DVector::from_fn(4, |i, _| {
+            if i == 0 {
+                Complex::one()
+            } else {
+                Complex::zero()
+            }
+        });
+        let bell = DVector::from_fn(4, |i, _| {
+            if i == 0 || i == 3 {
+                Complex::from_re(FRAC_1_SQRT_2)
+            } else {
+                Complex::zero()
+            }
+        });
+        assert_relative_eq!(mat * ket_00, bell, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn builder_makes_bell_state() {
+        use nalgebra::U4;
+        let circuit = Circuit::new(2).h(0).cnot(0, 1);
+        assert_eq!(circuit.ops(), &bell_ops()[..]);
+        let bell = circuit.run(QuantumRegister::<U4>::basis(0b00));
+        assert_relative_eq!(
+            bell.into_vector(),
+            QuantumRegister::<U4>::ghz().into_vector(),
+            epsilon = 1e-6
+        );
+        // Running it again replays the same gates
+        let bell = circuit.run(QuantumRegister::<U4>::basis(0b00));
+        assert_relative_eq!(
+            bell.probability(0b11),
+            0.5,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn builder_names_match_gates() {
+        use nalgebra::U8;
+        let reg = QuantumRegister::<U8>::basis(0b000);
+        let flipped = Circuit::new(3).x(0).x(2).cnot(2, 1).run(reg);
+        assert_eq!(flipped, QuantumRegister::basis(0b111));
+        let swapped = Circuit::new(3)
+            .swap(0, 2)
+            .run(QuantumRegister::<U8>::basis(0b100));
+        assert_eq!(swapped, QuantumRegister::basis(0b001));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Qubit 2 out of range for a 2 qubit circuit"
+    )]
+    fn builder_checks_range() {
+        Circuit::new(2).h(0).cnot(0, 2);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "A two qubit gate needs two different qubits, not 1 twice"
+    )]
+    fn builder_checks_distinct_qubits() {
+        Circuit::new(2).cnot(1, 1);
+    }
+
+    #[test]
+    fn noise() {
+        use crate::noise::depolarizing;
+        use nalgebra::U4;
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let mut rng = SmallRng::seed_from_u64(12);
+        let circuit = Circuit::new(2).h(0).cnot(0, 1);
+        let reg = || QuantumRegister::<U4>::basis(0);
+        assert_eq!(
+            circuit.run_with_rng(reg(), &mut rng),
+            circuit.run(reg())
+        );
+        let zero_noise =
+            circuit.clone().with_noise(depolarizing(0.0));
+        assert_relative_eq!(
+            zero_noise.run_with_rng(reg(), &mut rng).into_vector(),
+            circuit.run(reg()).into_vector(),
+            epsilon = 1e-6
+        );
+        // Full depolarizing after each gate leaves every outcome equally
+        // likely, where the noiseless circuit only gives |00⟩ and |11⟩
+        let noisy = circuit.with_noise(depolarizing(1.0));
+        let mut counts = [0; 4];
+        for _ in 0..4000 {
+            let out = noisy.run_with_rng(reg(), &mut rng);
+            counts[out.collapse_with_rng(&mut rng).bits as usize] +=
+                1;
+        }
+        for &count in &counts {
+            assert!(900 < count && count < 1100, "{:?}", counts);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() {
+        let bell = Circuit::new(2).h(0).cnot(0, 1);
+        let json = bell.to_json();
+        assert_eq!(
+            json,
+            r#"{"qubits":2,"ops":[{"gate":"h","targets":[0]},{"gate":"cnot","targets":[0,1]}]}"#
+        );
+        assert_eq!(Circuit::from_json(&json).unwrap(), bell);
+        let circuit = Circuit::new(3)
+            .swap(2, 0)
+            .t(1)
+            .cz(1, 2)
+            .unary(crate::gates::unitary::gates::rx(0.3), 2);
+        let back = Circuit::from_json(&circuit.to_json()).unwrap();
+        assert_relative_eq!(
+            circuit_matrix(back.ops(), 3),
+            circuit_matrix(circuit.ops(), 3),
+            epsilon = 1e-6
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trips_unnamed_binary_gates() {
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let mut rng = SmallRng::seed_from_u64(5);
+        let circuit = Circuit::new(3)
+            .binary(binary::gates::iswap(), 0, 2)
+            .binary(BinaryGate::random(&mut rng), 2, 1);
+        let json = circuit.to_json();
+        assert!(json.contains(r#""gate":"matrix""#), "{}", json);
+        let back = Circuit::from_json(&json).unwrap();
+        assert_relative_eq!(
+            circuit_matrix(back.ops(), 3),
+            circuit_matrix(circuit.ops(), 3),
+            epsilon = 1e-6
+        );
+        let not_unitary = format!(
+            r#"{{"qubits":2,"ops":[{{"gate":"matrix","targets":[0,1],"matrix":[{}]}}]}}"#,
+            vec![r#"{"re":1.0,"im":0.0}"#; 16].join(",")
+        );
+        let err = Circuit::from_json(&not_unitary).unwrap_err();
+        assert_eq!(err.to_string(), "gate matrix isn't unitary");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_rejects_bad_ops() {
+        let bad = [
+            r#"{"qubits":2,"ops":[{"gate":"frob","targets":[0]}]}"#,
+            r#"{"qubits":2,"ops":[{"gate":"h","targets":[2]}]}"#,
+            r#"{"qubits":2,"ops":[{"gate":"cnot","targets":[0]}]}"#,
+            r#"{"qubits":2,"ops":[{"gate":"cnot","targets":[1,1]}]}"#,
+            r#"{"qubits":2,"ops":[{"gate":"u","targets":[0]}]}"#,
+            r#"{"qubits":2,"ops":[{"gate":"h"}]}"#,
+            r#"{"qubits":2,"ops":[{"gate":"matrix","targets":[0,1]}]}"#,
+            r#"{"qubits":2,"ops":[{"gate":"h","targets":[0],"matrix":[{"re":1.0,"im":0.0}]}]}"#,
+            r#"{"qubits":2"#,
+        ];
+        for json in &bad {
+            assert!(Circuit::from_json(json).is_err(), "{}", json);
+        }
+        let err = Circuit::from_json(bad[0]).unwrap_err();
+        assert_eq!(err.to_string(), "unknown gate \"frob\"");
+    }
+
+    #[test]
+    fn draw_bell() {
+        let circuit = Circuit::new(2).h(0).cnot(0, 1);
+        assert_eq!(
+            circuit.draw(),
+            "q0: ─H──●─\n        │\nq1: ────⊕─"
+        );
+    }
+
+    #[test]
+    fn draw_spanning_gates() {
+        let circuit = Circuit::new(3)
+            .cnot(2, 0)
+            .t(1)
+            .swap(0, 1)
+            .unary(crate::gates::unitary::gates::rx(0.3), 2);
+        let expected = [
+            "q0: ─⊕─────×────",
+            "     │     │",
+            "q1: ─┼──T──×────",
+            "     │",
+            "q2: ─●────────U─",
+        ];
+        assert_eq!(circuit.draw(), expected.join("\n"));
+        assert_eq!(Circuit::new(1).draw(), "q0:");
+    }
+
+    #[test]
+    fn bell_qasm() {
+        let circuit = Circuit::new(2).h(0).cnot(0, 1);
+        assert_eq!(
+            circuit.to_qasm().unwrap(),
+            "OPENQASM 2.0;
+include \"qelib1.inc\";
+qreg q[2];
+creg c[2];
+h q[0];
+cx q[0],q[1];
+"
+        );
+    }
+
+    #[test]
+    fn qasm_gate_names() {
+        let circuit = Circuit::new(3)
+            .x(0)
+            .y(1)
+            .z(2)
+            .s(0)
+            .t(1)
+            .swap(2, 0)
+            .cz(1, 2)
+            .unary(UnaryGate::identity(), 1);
+        let body: Vec<_> = circuit
+            .to_qasm()
+            .unwrap()
+            .lines()
+            .skip(4)
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            body,
+            [
+                "x q[0];",
+                "y q[1];",
+                "z q[2];",
+                "s q[0];",
+                "t q[1];",
+                "swap q[2],q[0];",
+                "cz q[1],q[2];",
+                "u3(0,0,0) q[1];",
+            ]
+        );
+    }
+
+    #[test]
+    fn qasm_rejects_unnamed_binary_gates() {
+        let err = Circuit::new(2)
+            .h(0)
+            .binary(crate::gates::binary::gates::iswap(), 0, 1)
+            .to_qasm()
+            .unwrap_err();
+        assert_eq!(err, NoQasmName { index: 1 });
+        assert_eq!(
+            err.to_string(),
+            "Can't write operation 1 as OpenQASM, since it's a two qubit \
+             gate other than CNOT, SWAP or CZ"
+        );
+    }
+
+    #[test]
+    fn optimize_cancels_inverse_pairs() {
+        use nalgebra::U4;
+        let circuit = Circuit::new(2).h(0).h(0).x(1);
+        let optimized = circuit.optimize();
+        assert_eq!(optimized, Circuit::new(2).x(1));
+        let reg = || QuantumRegister::<U4>::basis(0b00);
+        assert_relative_eq!(
+            optimized.run(reg()).into_vector(),
+            circuit.run(reg()).into_vector(),
+            epsilon = 1e-6
+        );
+        // Cancelling the inner pair exposes the outer one, and gates on
+        // other qubits don't get in the way
+        let nested = Circuit::new(2)
+            .cnot(0, 1)
+            .h(0)
+            .s(0)
+            .unary(unitary::gates::s().dagger(), 0)
+            .z(1)
+            .h(0);
+        assert_eq!(
+            nested.optimize(),
+            Circuit::new(2).cnot(0, 1).z(1)
+        );
+        let symmetric = Circuit::new(2).cz(0, 1).cz(1, 0).swap(0, 1);
+        assert_eq!(symmetric.optimize(), Circuit::new(2).swap(0, 1));
+    }
+
+    #[test]
+    fn optimize_keeps_gates_that_dont_cancel() {
+        let circuits = [
+            Circuit::new(2).h(0).cnot(0, 1),
+            // T isn't its own inverse
+            Circuit::new(1).t(0).t(0),
+            Circuit::new(2).h(0).h(1),
+            // The CNOT between touches qubit 0
+            Circuit::new(2).x(0).cnot(1, 0).x(0),
+            // Reversing a CNOT's qubits changes the gate
+            Circuit::new(2).cnot(0, 1).cnot(1, 0),
+        ];
+        for circuit in &circuits {
+            assert_eq!(&circuit.optimize(), circuit);
+        }
+    }
+
+    #[test]
+    fn optimize_leaves_noisy_circuits_alone() {
+        use crate::noise::depolarizing;
+        let noisy =
+            Circuit::new(2).h(0).h(0).with_noise(depolarizing(0.5));
+        assert_eq!(noisy.optimize(), noisy);
+    }
+
+    #[test]
+    fn empty_circuit_is_identity() {
+        assert_eq!(circuit_matrix(&[], 3), DMatrix::identity(8, 8));
+    }
+}
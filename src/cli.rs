@@ -0,0 +1,199 @@
+//! Parsing for the `toy_quant` command line, which runs a small circuit
+//! from its arguments, as in
+//!
+//! ```text
+//! toy_quant --qubits 2 --gate h:0 --gate cnot:0,1 --shots 1000
+//! ```
+use crate::circuit::Circuit;
+use crate::complex::Complex;
+use crate::registers::quantum::collapse_amplitudes;
+use crate::Float;
+
+use clap::error::ErrorKind;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use rand::Rng;
+use std::error::Error;
+use std::fmt;
+
+/// The most qubits the command line will simulate
+pub const MAX_QUBITS: usize = 16;
+
+/// The shots to run when `--shots` isn't given
+pub const DEFAULT_SHOTS: usize = 1000;
+
+/// A circuit to run, and how many times to run it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Args {
+    pub circuit: Circuit,
+    pub shots: usize,
+}
+
+/// Errors from parsing the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    /// Arguments clap rejected, such as an unknown flag, a missing
+    /// `--qubits` or a count that isn't a number. `message` is clap's
+    /// report of the problem.
+    Usage { kind: ErrorKind, message: String },
+    /// The number of qubits is zero or too big to simulate
+    BadQubitCount(usize),
+    /// `--shots 0`, which would leave nothing to count
+    NoShots,
+    /// A gate spec that isn't `name:qubit` or `name:first,second`
+    BadGate { spec: String, reason: String },
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Usage { message, .. } => {
+                write!(f, "{}", message)
+            }
+            CliError::BadQubitCount(n) => write!(
+                f,
+                "can simulate 1 to {} qubits, not {}",
+                MAX_QUBITS, n
+            ),
+            CliError::NoShots => write!(f, "need at least one shot"),
+            CliError::BadGate { spec, reason } => {
+                write!(f, "bad gate {:?}: {}", spec, reason)
+            }
+        }
+    }
+}
+
+impl Error for CliError {}
+
+/// The clap description of the command line.
+///
+/// Gates are given as `name:qubit` for `h`, `x`, `y`, `z`, `s` and `t`,
+/// or `name:first,second` for `cnot`, `cz` and `swap`, and run in the
+/// order given.
+pub fn command() -> Command {
+    Command::new("toy_quant")
+        .about("Run a small quantum circuit and print the outcomes")
+        .arg(
+            Arg::new("qubits")
+                .long("qubits")
+                .value_name("N")
+                .help("The number of qubits, all starting in |0⟩")
+                .required(true)
+                .value_parser(value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("gate")
+                .long("gate")
+                .value_name("NAME:QUBITS")
+                .help("A gate to add, such as h:0 or cnot:0,1")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("shots")
+                .long("shots")
+                .value_name("N")
+                .help(format!(
+                    "How many times to run the circuit [default: {}]",
+                    DEFAULT_SHOTS
+                ))
+                .value_parser(value_parser!(usize)),
+        )
+}
+
+/// Parse the arguments after the program name, as [`command`]
+/// describes them.
+pub fn parse_args<I>(args: I) -> Result<Args, CliError>
+where
+    I: IntoIterator,
+    I::Item: Into<String>,
+{
+    let args = std::iter::once("toy_quant".to_string())
+        .chain(args.into_iter().map(Into::into));
+    let matches =
+        command().try_get_matches_from(args).map_err(|e| {
+            CliError::Usage {
+                kind: e.kind(),
+                message: e.to_string(),
+            }
+        })?;
+    args_from_matches(&matches)
+}
+
+/// Build the circuit from arguments clap has already parsed with
+/// [`command`]
+pub fn args_from_matches(
+    matches: &ArgMatches,
+) -> Result<Args, CliError> {
+    let n_qubits = *matches
+        .get_one::<usize>("qubits")
+        .expect("--qubits is required");
+    if n_qubits == 0 || n_qubits > MAX_QUBITS {
+        return Err(CliError::BadQubitCount(n_qubits));
+    }
+    let shots = matches
+        .get_one::<usize>("shots")
+        .copied()
+        .unwrap_or(DEFAULT_SHOTS);
+    if shots == 0 {
+        return Err(CliError::NoShots);
+    }
+    let circuit = matches
+        .get_many::<String>("gate")
+        .into_iter()
+        .flatten()
+        .try_fold(Circuit::new(n_qubits), |circuit, spec| {
+            add_gate(circuit, spec)
+        })?;
+    Ok(Args { circuit, shots })
+}
+
+/// Add the gate `spec` describes to the end of `circuit`
+fn add_gate(
+    circuit: Circuit,
+    spec: &str,
+) -> Result<Circuit, CliError> {
+    let bad = |reason: String| CliError::BadGate {
+        spec: spec.to_string(),
+        reason,
+    };
+    let (name, qubits) = match spec.find(':') {
+        Some(colon) => (&spec[..colon], &spec[colon + 1..]),
+        None => return Err(bad("expected name:qubits".to_string())),
+    };
+    let qubits = qubits
+        .split(',')
+        .map(|q| {
+            q.trim()
+                .parse()
+                .map_err(|_| bad(format!("{:?} isn't a qubit", q)))
+        })
+        .collect::<Result<Vec<usize>, _>>()?;
+    circuit.push_named(name, &qubits).map_err(bad)
+}
+
+/// Run `circuit` on `|0…0⟩` `shots` times, counting how often each
+/// basis state is measured. The result is indexed by basis state.
+///
+/// A noiseless circuit is only simulated once, but a noisy one is
+/// re-run for every shot, since each run can go differently.
+pub fn sample_counts<R: Rng>(
+    circuit: &Circuit,
+    shots: usize,
+    rng: &mut R,
+) -> Vec<usize> {
+    let run = |rng: &mut R| {
+        let mut amps = vec![Complex::zero(); 1 << circuit.n_qubits()];
+        amps[0] = Complex::one();
+        circuit.apply_to_slice(&mut amps, rng);
+        amps
+    };
+    let mut amps = run(rng);
+    let mut counts = vec![0; amps.len()];
+    for shot in 0..shots {
+        if shot != 0 && circuit.is_noisy() {
+            amps = run(rng);
+        }
+        let state = collapse_amplitudes(&amps, rng.gen::<Float>());
+        counts[state.bits as usize] += 1;
+    }
+    counts
+}
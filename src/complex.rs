@@ -1,33 +1,47 @@
 //! Complex numbers
 use approx::{AbsDiffEq, RelativeEq};
 use num_traits::identities::{One, Zero};
+use num_traits::Float;
+
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
 
 use std::ops::{
     Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub,
     SubAssign,
 };
 
-/// A complex number
+/// A complex number, with parts of type `F`. Leaving out `F` gives the
+/// [`Float`](crate::Float) numbers the rest of the crate uses, `f32`
+/// unless the `double-precision` feature is on.
 ///
 /// ```rust
 /// # use toy_quant::complex::Complex;
 /// let x = Complex::new(1.0, 0.0);
 /// assert_eq!(x, Complex::one());
+/// let y: Complex<f64> = Complex::new(1.0, 0.0);
+/// assert_eq!(y, Complex::one());
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Complex {
-    re: f32,
-    im: f32,
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct Complex<F = crate::Float> {
+    re: F,
+    im: F,
 }
 
-impl Complex {
+impl<F: Float> Complex<F> {
     /// Create a complex number from a real and imaginary part
-    pub fn new(re: f32, im: f32) -> Self {
+    pub fn new(re: F, im: F) -> Self {
         Self { re, im }
     }
 
     /// Create a complex number from a modulus and argument
-    pub fn mod_arg(r: f32, theta: f32) -> Self {
+    pub fn mod_arg(r: F, theta: F) -> Self {
         Self {
             re: r * theta.cos(),
             im: r * theta.sin(),
@@ -35,39 +49,77 @@ impl Complex {
     }
 
     /// Create a complex number e^ix, equivalent to mod_arg(1, x)
-    pub fn exp_ix(x: f32) -> Complex {
-        Complex::mod_arg(1.0, x)
+    pub fn exp_ix(x: F) -> Self {
+        Self::mod_arg(F::one(), x)
     }
 
     /// Create a complex number with a real part and no imaginary part
-    pub fn from_re(re: f32) -> Complex {
-        Self { re, im: 0.0 }
+    pub fn from_re(re: F) -> Self {
+        Self { re, im: F::zero() }
     }
 
     /// The complex number 0 + 0i
-    pub fn zero() -> Complex {
-        Complex::new(0.0, 0.0)
+    pub fn zero() -> Self {
+        Self::new(F::zero(), F::zero())
     }
 
     /// The complex number 1 + 0i
-    pub fn one() -> Complex {
-        Complex::new(1.0, 0.0)
+    pub fn one() -> Self {
+        Self::new(F::one(), F::zero())
     }
 
     /// √-1
-    pub fn i() -> Complex {
-        Complex::new(0.0, 1.0)
+    pub fn i() -> Self {
+        Self::new(F::zero(), F::one())
+    }
+
+    /// The real part
+    pub fn re(self) -> F {
+        self.re
+    }
+
+    /// The imaginary part
+    pub fn im(self) -> F {
+        self.im
     }
 
     /// |x|²
-    pub fn mag_square(self) -> f32 {
+    pub fn mag_square(self) -> F {
         self.re.powi(2) + self.im.powi(2)
     }
 
     /// |x|
-    pub fn norm(self) -> f32 {
+    pub fn norm(self) -> F {
         self.re.hypot(self.im)
     }
+
+    /// The argument (phase angle) in `(-π, π]`. The argument of 0 is 0.
+    pub fn arg(self) -> F {
+        self.im.atan2(self.re)
+    }
+
+    /// The modulus and argument `(r, θ)`, undoing
+    /// [`mod_arg`](Self::mod_arg)
+    pub fn to_polar(self) -> (F, F) {
+        (self.norm(), self.arg())
+    }
+
+    /// e^x
+    pub fn exp(self) -> Self {
+        Self::mod_arg(self.re.exp(), self.im)
+    }
+
+    /// The principal square root, with argument in `(-π/2, π/2]`
+    pub fn sqrt(self) -> Self {
+        let two = F::one() + F::one();
+        Self::mod_arg(self.norm().sqrt(), self.arg() / two)
+    }
+
+    /// x^n, on the principal branch
+    pub fn powf(self, n: F) -> Self {
+        Self::mod_arg(self.norm().powf(n), self.arg() * n)
+    }
+
     /// The complex conjugate. Re(x) - i Im (x). a-bi
     pub fn conj(self) -> Self {
         Self {
@@ -77,7 +129,97 @@ impl Complex {
     }
 }
 
-impl Add<Complex> for Complex {
+/// Formats as `a+bi`, leaving out a part that's zero. The precision is
+/// used for both parts, so `{:.3}` gives `0.707-0.500i`.
+impl<F: Float + fmt::Display> fmt::Display for Complex<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let part = |f: &mut fmt::Formatter, x: F| match f.precision()
+        {
+            Some(precision) => write!(f, "{:.*}", precision, x),
+            None => write!(f, "{}", x),
+        };
+        if self.im == F::zero() {
+            return part(f, self.re);
+        }
+        if self.re != F::zero() {
+            part(f, self.re)?;
+            if self.im > F::zero() {
+                write!(f, "+")?;
+            }
+        }
+        part(f, self.im)?;
+        write!(f, "i")
+    }
+}
+
+/// An error from parsing a [`Complex`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseComplexError {
+    /// There was nothing to parse
+    Empty,
+    /// One of the parts wasn't a valid number
+    InvalidNumber,
+}
+
+impl fmt::Display for ParseComplexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseComplexError::Empty => {
+                write!(f, "cannot parse complex from empty string")
+            }
+            ParseComplexError::InvalidNumber => {
+                write!(f, "invalid complex number literal")
+            }
+        }
+    }
+}
+
+impl Error for ParseComplexError {}
+
+/// Parses `a`, `bi` and `a±bi`, where `b` can be left out for `±i`.
+///
+/// ```rust
+/// # use toy_quant::complex::Complex;
+/// assert_eq!("3-2i".parse(), Ok(Complex::new(3.0, -2.0)));
+/// assert_eq!(" -i ".parse::<Complex>(), Ok(-Complex::i()));
+/// ```
+impl<F: Float + FromStr> FromStr for Complex<F> {
+    type Err = ParseComplexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseComplexError::Empty);
+        }
+        let parse = |x: &str| {
+            x.parse::<F>()
+                .map_err(|_| ParseComplexError::InvalidNumber)
+        };
+        let body = match s.strip_suffix('i') {
+            Some(body) => body,
+            None => return parse(s).map(Complex::from_re),
+        };
+        // The sign between the parts, skipping a leading sign and the
+        // sign of an exponent
+        let split = body.char_indices().rev().find(|&(i, c)| {
+            (c == '+' || c == '-')
+                && i != 0
+                && !body[..i].ends_with(['e', 'E'])
+        });
+        let (re, im) = match split {
+            Some((i, _)) => (parse(&body[..i])?, &body[i..]),
+            None => (F::zero(), body),
+        };
+        let im = match im {
+            "" | "+" => F::one(),
+            "-" => -F::one(),
+            im => parse(im)?,
+        };
+        Ok(Complex::new(re, im))
+    }
+}
+
+impl<F: Float> Add<Complex<F>> for Complex<F> {
     type Output = Self;
     fn add(self, other: Self) -> Self {
         Self {
@@ -87,7 +229,7 @@ impl Add<Complex> for Complex {
     }
 }
 
-impl Sub<Complex> for Complex {
+impl<F: Float> Sub<Complex<F>> for Complex<F> {
     type Output = Self;
     fn sub(self, other: Self) -> Self {
         Self {
@@ -97,7 +239,7 @@ impl Sub<Complex> for Complex {
     }
 }
 
-impl Mul<Complex> for Complex {
+impl<F: Float> Mul<Complex<F>> for Complex<F> {
     type Output = Self;
     fn mul(self, other: Self) -> Self {
         Self {
@@ -107,9 +249,9 @@ impl Mul<Complex> for Complex {
     }
 }
 
-impl Mul<f32> for Complex {
+impl<F: Float> Mul<F> for Complex<F> {
     type Output = Self;
-    fn mul(self, other: f32) -> Self {
+    fn mul(self, other: F) -> Self {
         Self {
             re: self.re * other,
             im: self.im * other,
@@ -117,26 +259,71 @@ impl Mul<f32> for Complex {
     }
 }
 
-impl Mul<Complex> for f32 {
-    type Output = Complex;
-    fn mul(self, other: Complex) -> Complex {
+impl<F: Float> Div<F> for Complex<F> {
+    type Output = Self;
+    fn div(self, other: F) -> Self {
+        Self {
+            re: self.re / other,
+            im: self.im / other,
+        }
+    }
+}
+
+impl<F: Float> Add<F> for Complex<F> {
+    type Output = Self;
+    fn add(self, other: F) -> Self {
+        Self {
+            re: self.re + other,
+            im: self.im,
+        }
+    }
+}
+
+impl<F: Float> Sub<F> for Complex<F> {
+    type Output = Self;
+    fn sub(self, other: F) -> Self {
+        Self {
+            re: self.re - other,
+            im: self.im,
+        }
+    }
+}
+
+// Coherence won't allow a blanket impl for the scalar on the left, so
+// these are written out per float type.
+impl Mul<Complex<f32>> for f32 {
+    type Output = Complex<f32>;
+    fn mul(self, other: Complex<f32>) -> Complex<f32> {
         other * self
     }
 }
 
-impl From<f32> for Complex {
-    fn from(num: f32) -> Complex {
+impl Mul<Complex<f64>> for f64 {
+    type Output = Complex<f64>;
+    fn mul(self, other: Complex<f64>) -> Complex<f64> {
+        other * self
+    }
+}
+
+impl<F: Float> From<F> for Complex<F> {
+    fn from(num: F) -> Self {
         Complex::from_re(num)
     }
 }
 
-impl From<u8> for Complex {
-    fn from(num: u8) -> Complex {
+impl From<u8> for Complex<f32> {
+    fn from(num: u8) -> Self {
+        Complex::from_re(num.into())
+    }
+}
+
+impl From<u8> for Complex<f64> {
+    fn from(num: u8) -> Self {
         Complex::from_re(num.into())
     }
 }
 
-impl Div<Complex> for Complex {
+impl<F: Float> Div<Complex<F>> for Complex<F> {
     type Output = Self;
     // We have tests for this, and clippy freaks out
     // when I have a addition in a division function.
@@ -157,34 +344,34 @@ impl Div<Complex> for Complex {
     }
 }
 
-impl AddAssign for Complex {
-    fn add_assign(&mut self, other: Complex) {
+impl<F: Float> AddAssign for Complex<F> {
+    fn add_assign(&mut self, other: Self) {
         *self = *self + other;
     }
 }
 
-impl SubAssign for Complex {
-    fn sub_assign(&mut self, other: Complex) {
+impl<F: Float> SubAssign for Complex<F> {
+    fn sub_assign(&mut self, other: Self) {
         *self = *self - other;
     }
 }
 
-impl MulAssign for Complex {
-    fn mul_assign(&mut self, other: Complex) {
+impl<F: Float> MulAssign for Complex<F> {
+    fn mul_assign(&mut self, other: Self) {
         *self = *self * other;
     }
 }
 
-impl DivAssign for Complex {
-    fn div_assign(&mut self, other: Complex) {
+impl<F: Float> DivAssign for Complex<F> {
+    fn div_assign(&mut self, other: Self) {
         *self = *self / other;
     }
 }
 
-impl Neg for Complex {
-    type Output = Complex;
+impl<F: Float> Neg for Complex<F> {
+    type Output = Self;
 
-    fn neg(self) -> Complex {
+    fn neg(self) -> Self {
         Self {
             re: -self.re,
             im: -self.im,
@@ -192,52 +379,64 @@ impl Neg for Complex {
     }
 }
 
-impl Zero for Complex {
+/// Complex numbers compare equal exactly when both parts do, so this
+/// holds except for NaN, which isn't equal to anything. Keep NaN out of
+/// sets and map keys.
+impl<F: Float> Eq for Complex<F> {}
+
+/// Hashes the bit patterns of the parts, so it agrees with `==`: `0`
+/// and `-0` are equal and hash the same.
+impl<F: Float> Hash for Complex<F> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for &x in &[self.re, self.im] {
+            // Adding 0 turns -0 into 0
+            (x + F::zero()).integer_decode().hash(state);
+        }
+    }
+}
+
+impl<F: Float> Zero for Complex<F> {
     fn zero() -> Self {
-        0.0.into()
+        Complex::zero()
     }
     fn is_zero(&self) -> bool {
         self == &Self::zero()
     }
 }
 
-impl One for Complex {
+impl<F: Float> One for Complex<F> {
     fn one() -> Self {
-        (1.0).into()
+        Complex::one()
     }
     fn is_one(&self) -> bool {
         self == &Self::one()
     }
 }
 
-impl AbsDiffEq for Complex {
-    type Epsilon = <f32 as AbsDiffEq>::Epsilon;
-    fn default_epsilon() -> Self::Epsilon {
-        f32::default_epsilon()
+impl<F: Float + AbsDiffEq<Epsilon = F>> AbsDiffEq for Complex<F> {
+    type Epsilon = F;
+    fn default_epsilon() -> F {
+        F::default_epsilon()
     }
-    fn abs_diff_eq(
-        &self,
-        other: &Self,
-        epsilon: Self::Epsilon,
-    ) -> bool {
-        f32::abs_diff_eq(&self.re, &other.re, epsilon)
-            && f32::abs_diff_eq(&self.im, &other.im, epsilon)
+    fn abs_diff_eq(&self, other: &Self, epsilon: F) -> bool {
+        F::abs_diff_eq(&self.re, &other.re, epsilon)
+            && F::abs_diff_eq(&self.im, &other.im, epsilon)
     }
 }
 
-impl RelativeEq for Complex {
-    fn default_max_relative() -> <f32 as AbsDiffEq>::Epsilon {
-        f32::default_max_relative()
+impl<F: Float + RelativeEq<Epsilon = F>> RelativeEq for Complex<F> {
+    fn default_max_relative() -> F {
+        F::default_max_relative()
     }
 
     fn relative_eq(
         &self,
         other: &Self,
-        epsilon: Self::Epsilon,
-        max_relative: Self::Epsilon,
+        epsilon: F,
+        max_relative: F,
     ) -> bool {
-        f32::relative_eq(&self.re, &other.re, epsilon, max_relative)
-            && f32::relative_eq(
+        F::relative_eq(&self.re, &other.re, epsilon, max_relative)
+            && F::relative_eq(
                 &self.im,
                 &other.im,
                 epsilon,
@@ -248,6 +447,222 @@ impl RelativeEq for Complex {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use approx::{abs_diff_eq, assert_relative_eq, relative_eq};
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    #[test]
+    fn display() {
+        assert_eq!(format!("{}", Complex::new(1.0, -2.0)), "1-2i");
+        assert_eq!(format!("{}", Complex::new(0.5, 0.5)), "0.5+0.5i");
+        assert_eq!(
+            format!("{}", Complex::new(0.5, -0.5)),
+            "0.5-0.5i"
+        );
+        assert_eq!(format!("{}", Complex::from_re(-3.0)), "-3");
+        assert_eq!(format!("{}", Complex::<f32>::i()), "1i");
+        assert_eq!(format!("{}", -Complex::<f32>::i()), "-1i");
+        assert_eq!(format!("{}", Complex::<f32>::zero()), "0");
+        assert_eq!(
+            format!("{:.3}", Complex::new(0.70710677, -0.5)),
+            "0.707-0.500i"
+        );
+        assert_eq!(format!("{:.1}", Complex::from_re(2.0)), "2.0");
+    }
+
+    #[test]
+    fn parse() {
+        let parse = |s: &str| s.parse::<Complex>();
+        assert_eq!(parse("0.707+0.0i"), Ok(Complex::new(0.707, 0.0)));
+        assert_eq!(parse("1"), Ok(Complex::one()));
+        assert_eq!(parse("-2.5"), Ok(Complex::from_re(-2.5)));
+        assert_eq!(parse("i"), Ok(Complex::i()));
+        assert_eq!(parse("-i"), Ok(-Complex::i()));
+        assert_eq!(parse("+i"), Ok(Complex::i()));
+        assert_eq!(parse("2i"), Ok(Complex::new(0.0, 2.0)));
+        assert_eq!(parse("3-2i"), Ok(Complex::new(3.0, -2.0)));
+        assert_eq!(parse("-3+i"), Ok(Complex::new(-3.0, 1.0)));
+        assert_eq!(
+            parse("1e-3-2E+1i"),
+            Ok(Complex::new(1e-3, -20.0))
+        );
+        assert_eq!(parse("  4+5i\n"), Ok(Complex::new(4.0, 5.0)));
+
+        assert_eq!(parse(""), Err(ParseComplexError::Empty));
+        assert_eq!(parse("   "), Err(ParseComplexError::Empty));
+        for bad in
+            &["abc", "1+", "1+2", "i2", "1+2ii", "3-2j", "1 + 2i"]
+        {
+            assert_eq!(
+                parse(bad),
+                Err(ParseComplexError::InvalidNumber),
+                "{:?}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn parse_display_round_trip() {
+        for &c in &[
+            Complex::new(1.0, -2.0),
+            Complex::new(0.70710677, 0.70710677),
+            Complex::new(-0.25, 1e-7),
+            Complex::from_re(3.5),
+            Complex::new(0.0, -1.0),
+            Complex::zero(),
+        ] {
+            assert_eq!(c.to_string().parse(), Ok(c));
+        }
+    }
+
+    #[test]
+    fn arg() {
+        assert_relative_eq!(Complex::new(1.0, 1.0).arg(), FRAC_PI_4);
+        assert_relative_eq!(
+            Complex::new(-1.0, 1.0).arg(),
+            3.0 * FRAC_PI_4
+        );
+        assert_relative_eq!(
+            Complex::new(-1.0, -1.0).arg(),
+            -3.0 * FRAC_PI_4
+        );
+        assert_relative_eq!(
+            Complex::new(1.0, -1.0).arg(),
+            -FRAC_PI_4
+        );
+        assert_relative_eq!(Complex::<f32>::i().arg(), FRAC_PI_2);
+        assert_relative_eq!(Complex::from_re(-1.0).arg(), PI);
+        assert_eq!(Complex::<f32>::zero().arg(), 0.0);
+        for &theta in &[0.3, 1.7, 3.0, -0.2, -2.5] {
+            assert_relative_eq!(
+                Complex::mod_arg(2.0, theta).arg(),
+                theta
+            );
+        }
+    }
+
+    #[test]
+    fn to_polar() {
+        let (r, theta) = Complex::mod_arg(2.0f32, 0.7).to_polar();
+        assert_relative_eq!(r, 2.0);
+        assert_relative_eq!(theta, 0.7, epsilon = 1e-6);
+        assert_eq!(Complex::<f32>::zero().to_polar(), (0.0, 0.0));
+        assert_eq!(Complex::from_re(-3.0f32).to_polar(), (3.0, PI));
+        // Angles outside (-π, π] wrap around
+        for &(theta, wrapped) in &[
+            (4.0, 4.0 - 2.0 * PI),
+            (-3.5, 2.0 * PI - 3.5),
+            (7.0, 7.0 - 2.0 * PI),
+        ] {
+            let (r, arg) = Complex::mod_arg(0.5f32, theta).to_polar();
+            assert_relative_eq!(r, 0.5, epsilon = 1e-6);
+            assert_relative_eq!(arg, wrapped, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn exp() {
+        for &x in &[0.0, 0.5, -1.2, 3.0] {
+            assert_relative_eq!(
+                Complex::new(0.0, x).exp(),
+                Complex::exp_ix(x)
+            );
+        }
+        assert_relative_eq!(
+            Complex::one().exp(),
+            Complex::from_re(1f32.exp())
+        );
+        assert_relative_eq!(
+            Complex::new(2.0, PI).exp(),
+            Complex::from_re(-(2f32.exp())),
+            epsilon = 1e-6
+        );
+        assert_eq!(Complex::<f32>::zero().exp(), Complex::one());
+    }
+    #[test]
+    fn sqrt() {
+        assert_relative_eq!(
+            Complex::new(-1.0, 0.0).sqrt(),
+            Complex::i(),
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(Complex::from_re(4.0).sqrt(), 2.0.into());
+        for &c in &[
+            Complex::new(3.0, 4.0),
+            Complex::new(-2.0, 0.5),
+            Complex::new(0.1, -0.7),
+            Complex::i(),
+        ] {
+            assert_relative_eq!(
+                c.sqrt() * c.sqrt(),
+                c,
+                epsilon = 1e-6
+            );
+            assert!(c.sqrt().re() >= 0.0);
+        }
+    }
+
+    #[test]
+    fn powf() {
+        let c = Complex::new(1.5, -0.5);
+        assert_relative_eq!(c.powf(2.0), c * c, epsilon = 1e-6);
+        assert_relative_eq!(c.powf(0.5), c.sqrt());
+        assert_relative_eq!(c.powf(0.0), Complex::one());
+        assert_relative_eq!(
+            Complex::i().powf(3.0),
+            -Complex::i(),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn double_precision() {
+        let a: Complex<f64> = Complex::new(0.1, 0.2);
+        let b = Complex::new(0.3, -0.4);
+        assert_relative_eq!(a * b / b, a, epsilon = 1e-15);
+        assert_relative_eq!(
+            Complex::<f64>::exp_ix(std::f64::consts::PI),
+            -Complex::one(),
+            epsilon = 1e-15
+        );
+        assert_eq!(2.0 * a, a + a);
+        assert_eq!("0.1+0.2i".parse(), Ok(a));
+    }
+
+    #[test]
+    fn divide_real() {
+        let a = Complex::new(3.0, 2.0);
+        assert_eq!(a / 4.0, Complex::new(3.0 / 4.0, 2.0 / 4.0));
+        let a = Complex::new(-6.0, -3.0);
+        assert_eq!(a / -3.0, Complex::new(2.0, 1.0));
+        let a = Complex::new(2.0, -1.0);
+        assert_eq!(a / 0.5, Complex::new(4.0, -2.0));
+        let a = Complex::new(1.0, -1.0) / 0.0;
+        assert_eq!(a.re(), f32::INFINITY);
+        assert_eq!(a.im(), f32::NEG_INFINITY);
+        assert!((Complex::zero() / 0.0).re().is_nan());
+    }
+
+    #[test]
+    fn add_sub_real() {
+        let a = Complex::new(3.0, 2.0);
+        assert_eq!(a + 1.5, Complex::new(4.5, 2.0));
+        assert_eq!(a - 1.5, Complex::new(1.5, 2.0));
+        assert_eq!(a + 1.5, a + Complex::from_re(1.5));
+    }
+
+    #[test]
+    fn approx_eq() {
+        let a: Complex<f32> = Complex::new(0.5, -0.25);
+        let close = Complex::new(0.5 + 1e-8, -0.25 - 1e-8);
+        let far = Complex::new(0.5, -0.25 + 1e-3);
+        assert_relative_eq!(a, close);
+        assert_relative_eq!(a, far, epsilon = 1e-2);
+        assert!(!relative_eq!(a, far));
+        assert!(!relative_eq!(a, Complex::new(0.5 + 1e-3, -0.25)));
+        assert!(!abs_diff_eq!(a, far));
+    }
+
     #[test]
     fn divide() {
         let a = Complex::new(3.0, 2.0);
@@ -266,4 +681,25 @@ mod tests {
         let b = Complex::new(4.0, 6.0);
         assert_eq!(a / b, Complex::new(-21.0 / 26.0, 6.0 / 13.0));
     }
+
+    #[test]
+    fn hash_deduplicates() {
+        use std::collections::HashSet;
+        let set: HashSet<Complex> = [
+            Complex::new(0.5, -1.0),
+            Complex::new(0.5, -1.0),
+            Complex::new(0.0, 0.0),
+            Complex::new(-0.0, 0.0),
+            Complex::new(0.0, -0.0),
+            Complex::i(),
+            "1i".parse().unwrap(),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&Complex::new(0.5, -1.0)));
+        assert!(set.contains(&Complex::zero()));
+        assert!(!set.contains(&Complex::one()));
+    }
 }
@@ -1,8 +1,21 @@
+use std::fmt;
+use std::ops::Mul;
+
 use crate::complex::Complex;
+use crate::gates::unitary::{self, UnaryGate};
+use crate::gates::NotUnitary;
+use crate::linalg;
+use crate::qubit::Qubit;
+use crate::registers::kron_mat;
 use crate::registers::quantum::QuantumRegister;
+use crate::Float;
 
-use approx::assert_relative_eq;
-use nalgebra::U4;
+use approx::relative_eq;
+use nalgebra::allocator::Allocator;
+use nalgebra::default_allocator::DefaultAllocator;
+use nalgebra::dimension::DimName;
+use nalgebra::{DMatrix, U2, U4};
+use rand::Rng;
 
 type Matrix = nalgebra::Matrix4<Complex>;
 type MatrixU8 = nalgebra::Matrix4<u8>;
@@ -10,14 +23,42 @@ type Register2 = QuantumRegister<U4>;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct BinaryGate {
-    mat: Matrix,
+    pub(crate) mat: Matrix,
 }
 
 impl BinaryGate {
     pub fn new(mat: Matrix) -> Self {
-        let x = mat * mat.transpose().map(|x| x.conj());
-        assert_relative_eq!(x, Matrix::identity());
-        Self { mat }
+        match Self::try_new(mat) {
+            Ok(gate) => gate,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Create a binary gate, or `Err` if `mat` is not unitary
+    pub fn try_new(mat: Matrix) -> Result<Self, NotUnitary> {
+        if is_unitary(&mat) {
+            Ok(Self { mat })
+        } else {
+            Err(NotUnitary)
+        }
+    }
+
+    /// The phase oracle `U_f|x⟩ = (-1)^f(x) |x⟩` for a function on the
+    /// basis states `0..4`, as used by
+    /// [Deutsch–Jozsa](https://en.wikipedia.org/wiki/Deutsch%E2%80%93Jozsa_algorithm)
+    pub fn phase_oracle(f: impl Fn(usize) -> bool) -> Self {
+        let mut mat = Matrix::identity();
+        for x in 0..4 {
+            if f(x) {
+                mat[(x, x)] = -Complex::one();
+            }
+        }
+        Self::new(mat)
+    }
+
+    /// The gate that does nothing
+    pub fn identity() -> Self {
+        Self::new(Matrix::identity())
     }
 
     pub fn new_u8(mat: MatrixU8) -> Self {
@@ -25,16 +66,339 @@ impl BinaryGate {
     }
 
     pub fn apply(&self, qubits: Register2) -> Register2 {
-        Register2::from_vector(self.mat * qubits.into_vector())
+        Register2::from_vector_unchecked(
+            self.mat * qubits.into_vector(),
+        )
+    }
+
+    /// [`apply`](Self::apply) the gate to each of `regs`. With the
+    /// `rayon` feature this runs in parallel, with the same results.
+    pub fn apply_many(&self, regs: &[Register2]) -> Vec<Register2> {
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            regs.par_iter()
+                .map(|reg| self.apply(reg.clone()))
+                .collect()
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            regs.iter().map(|reg| self.apply(reg.clone())).collect()
+        }
+    }
+
+    /// A [Haar-random](https://en.wikipedia.org/wiki/Haar_measure) gate,
+    /// drawn uniformly from all two-qubit unitaries.
+    pub fn random<R: Rng>(rng: &mut R) -> BinaryGate {
+        // Orthonormalizing the columns of a matrix of standard complex
+        // normals gives the Q of its QR decomposition, which is
+        // Haar-distributed.
+        let mut mat = Matrix::from_fn(|_, _| {
+            // Box-Muller transform
+            let r = (-2.0 * (1.0 - rng.gen::<Float>()).ln()).sqrt();
+            let theta = 2.0 * crate::consts::PI * rng.gen::<Float>();
+            Complex::mod_arg(r, theta)
+        });
+        linalg::orthonormalize(&mut mat);
+        Self::new(mat)
+    }
+
+    /// Run the gate on qubits `first` and `second` of a larger register,
+    /// where `first` is the gate's high-order qubit (the control of
+    /// [`cnot`](gates::cnot)).
+    ///
+    /// Qubit 0 is the most significant bit of the basis state. Qubits that
+    /// aren't adjacent are swapped next to each other for the gate, then
+    /// swapped back.
+    pub fn apply_to<N: DimName>(
+        &self,
+        reg: QuantumRegister<N>,
+        first: usize,
+        second: usize,
+    ) -> QuantumRegister<N>
+    where
+        DefaultAllocator: Allocator<Complex, N>,
+    {
+        let mut qubits = reg.into_vector();
+        self.apply_to_slice(qubits.as_mut_slice(), first, second);
+        QuantumRegister::from_vector_unchecked(qubits)
+    }
+
+    pub(crate) fn apply_to_slice(
+        &self,
+        amps: &mut [Complex],
+        first: usize,
+        second: usize,
+    ) {
+        let n_qubits = amps.len().trailing_zeros() as usize;
+        for &q in &[first, second] {
+            assert!(
+                q < n_qubits,
+                "Qubit {} out of range for a {} qubit register",
+                q,
+                n_qubits
+            );
+        }
+        assert_ne!(first, second, "A gate needs two distinct qubits");
+        // Each swap is given by the higher of the two adjacent qubits
+        let (swaps, gate, high): (Vec<usize>, _, _) = if first
+            < second
+        {
+            // Move `second` down to just after `first`
+            ((first + 1..second).rev().collect(), self.clone(), first)
+        } else {
+            // Move `second` up to just before `first`, and flip the gate
+            // to match the order the qubits end up in
+            ((second..first - 1).collect(), self.swap(), first - 1)
+        };
+        let swap = gates::swap();
+        for &q in &swaps {
+            swap.apply_adjacent(amps, q);
+        }
+        gate.apply_adjacent(amps, high);
+        for &q in swaps.iter().rev() {
+            swap.apply_adjacent(amps, q);
+        }
+    }
+
+    /// The operator on a `total_qubits` qubit register that runs the
+    /// gate on `qubits` and leaves the others alone, `I ⊗ G ⊗ I`.
+    ///
+    /// For now the two qubits must be adjacent, with the gate's
+    /// high-order qubit first, as in `(1, 2)`.
+    /// [`apply_to`](Self::apply_to) runs the gate on any pair without
+    /// building the full matrix.
+    pub fn embed(
+        &self,
+        total_qubits: usize,
+        qubits: (usize, usize),
+    ) -> DMatrix<Complex> {
+        let (first, second) = qubits;
+        assert_eq!(
+            first + 1,
+            second,
+            "Embedded gates need adjacent qubits, not {} and {}",
+            first,
+            second
+        );
+        assert!(
+            second < total_qubits,
+            "Qubit {} out of range for a {} qubit register",
+            second,
+            total_qubits
+        );
+        let before =
+            DMatrix::<Complex>::identity(1 << first, 1 << first);
+        let after = DMatrix::<Complex>::identity(
+            1 << (total_qubits - 1 - second),
+            1 << (total_qubits - 1 - second),
+        );
+        let gate =
+            DMatrix::from_iterator(4, 4, self.mat.iter().cloned());
+        kron_mat(&kron_mat(&before, &gate), &after)
+    }
+
+    /// Run the gate on qubits `high` and `high + 1`
+    fn apply_adjacent(&self, amps: &mut [Complex], high: usize) {
+        let n_qubits = amps.len().trailing_zeros() as usize;
+        let high_bit = 1 << (n_qubits - 1 - high);
+        let low_bit = high_bit >> 1;
+        for i in
+            (0..amps.len()).filter(|i| i & (high_bit | low_bit) == 0)
+        {
+            let idx = [
+                i,
+                i | low_bit,
+                i | high_bit,
+                i | high_bit | low_bit,
+            ];
+            let old = idx.map(|j| amps[j]);
+            for (row, &j) in idx.iter().enumerate() {
+                amps[j] = (0..4).fold(Complex::zero(), |acc, col| {
+                    acc + self.mat[(row, col)] * old[col]
+                });
+            }
+        }
     }
 
     pub fn compose(&self, other: &Self) -> Self {
         Self::new(self.mat * other.mat)
     }
 
+    /// The gate run `n` times in a row, by repeated squaring. This is
+    /// the identity for `n == 0`.
+    pub fn pow(&self, mut n: u32) -> Self {
+        let mut result = Matrix::identity();
+        let mut square = self.mat;
+        while n > 0 {
+            if n & 1 == 1 {
+                result *= square;
+                linalg::orthonormalize(&mut result);
+            }
+            square *= square;
+            // A product of unitaries is unitary, but squaring doubles
+            // the rounding error each time, so pull it back to unitary
+            linalg::orthonormalize(&mut square);
+            n >>= 1;
+        }
+        Self::new(result)
+    }
+
+    /// The controlled version of `gate.pow(k)`, running `gate` `k` times
+    /// on the low-order qubit when the high-order one is `|1⟩`. Phase
+    /// estimation uses these with `k` a power of two.
+    pub fn controlled_power(gate: &UnaryGate, k: u32) -> Self {
+        let mut mat = Matrix::identity();
+        mat.fixed_slice_mut::<U2, U2>(2, 2)
+            .copy_from(&gate.pow(k).mat);
+        Self::new(mat)
+    }
+
+    /// Run `top` and `bottom` side by side. `top` acts on the
+    /// high-order qubit, the first one passed to
+    /// [`from_2_qubits`](QuantumRegister::from_2_qubits), and `bottom` on
+    /// the low-order one.
+    pub fn from_layers(top: &UnaryGate, bottom: &UnaryGate) -> Self {
+        top.tensor(bottom)
+    }
+
+    /// The conjugate transpose, which undoes the gate
+    pub fn dagger(&self) -> Self {
+        Self::new(self.mat.transpose().map(|x| x.conj()))
+    }
+
+    /// The inverse gate, the same as [`dagger`](Self::dagger) since the
+    /// gate is unitary
+    pub fn inverse(&self) -> Self {
+        self.dagger()
+    }
+
     pub fn swap(&self) -> Self {
         gates::swap().compose(self).compose(&gates::swap())
     }
+
+    pub fn matrix(&self) -> &Matrix {
+        &self.mat
+    }
+
+    /// The sum of the diagonal of the gate's matrix
+    pub fn trace(&self) -> Complex {
+        trace(&self.mat)
+    }
+
+    /// The determinant of the gate's matrix. Always has modulus 1.
+    pub fn determinant(&self) -> Complex {
+        linalg::determinant(&self.mat)
+    }
+
+    /// How closely this gate implements `target`, as `|Tr(U† V)|² / 16`.
+    ///
+    /// This is 1 for gates that are equal up to a global phase.
+    pub fn process_fidelity(&self, target: &BinaryGate) -> Float {
+        let overlap =
+            self.mat.transpose().map(|x| x.conj()) * target.mat;
+        trace(&overlap).mag_square() / 16.0
+    }
+
+    /// The conventional name of the gate, if it's one of the standard
+    /// gates.
+    pub(crate) fn name(&self) -> Option<&'static str> {
+        let known = [
+            ("CNOT", gates::cnot()),
+            ("SWAP", gates::swap()),
+            ("CZ", gates::cz()),
+        ];
+        known
+            .iter()
+            .find(|(_, gate)| relative_eq!(self.mat, gate.mat))
+            .map(|(name, _)| *name)
+    }
+}
+
+/// `&gate * reg` is `gate.apply(reg)`
+impl Mul<Register2> for &BinaryGate {
+    type Output = Register2;
+    fn mul(self, qubits: Register2) -> Register2 {
+        self.apply(qubits)
+    }
+}
+
+impl Mul<Register2> for BinaryGate {
+    type Output = Register2;
+    fn mul(self, qubits: Register2) -> Register2 {
+        self.apply(qubits)
+    }
+}
+
+/// `&a * &b` is `a.compose(&b)`, running `b` first
+impl Mul for &BinaryGate {
+    type Output = BinaryGate;
+    fn mul(self, other: &BinaryGate) -> BinaryGate {
+        self.compose(other)
+    }
+}
+
+impl_approx_eq!(BinaryGate, Matrix);
+
+/// Prints the name of standard gates, and the matrix of anything else
+impl fmt::Display for BinaryGate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(name) = self.name() {
+            return write!(f, "{}", name);
+        }
+        for row in 0..4 {
+            if row != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "[")?;
+            for col in 0..4 {
+                if col != 0 {
+                    write!(f, ", ")?;
+                }
+                fmt::Display::fmt(&self.mat[(row, col)], f)?;
+            }
+            write!(f, "]")?;
+        }
+        Ok(())
+    }
+}
+
+/// How well `gate` copies each of `states` from qubit 0 onto a `|0⟩` in
+/// qubit 1: the fidelity of the worse of the two output copies with the
+/// input, for the worst of the states.
+///
+/// The [no-cloning theorem](https://en.wikipedia.org/wiki/No-cloning_theorem)
+/// means this can't be 1 for a set of states with a non-orthogonal
+/// pair, and no gate can beat 5/6 for every state at once.
+pub fn best_cloning_fidelity(
+    gate: &BinaryGate,
+    states: &[Qubit],
+) -> Float {
+    states
+        .iter()
+        .map(|state| {
+            let out = gate.apply(Register2::from_2_qubits(
+                state.clone(),
+                Qubit::zero(),
+            ));
+            let fidelity = |qubit| {
+                let rho = out.partial_trace(qubit);
+                let bra = state.inner.transpose().map(|x| x.conj());
+                (bra * rho * state.inner)[0].re()
+            };
+            fidelity(0).min(fidelity(1))
+        })
+        .fold(1.0, Float::min)
+}
+
+/// Whether `mat` is [unitary](https://en.wikipedia.org/wiki/Unitary_matrix),
+/// to within `1e-6`, and so can be made into a gate
+pub fn is_unitary(mat: &Matrix) -> bool {
+    linalg::is_unitary(mat)
+}
+
+fn trace(mat: &Matrix) -> Complex {
+    (0..4).fold(Complex::zero(), |acc, i| acc + mat[(i, i)])
 }
 
 pub mod gates {
@@ -66,11 +430,492 @@ pub mod gates {
             1, 0, 0, 0, 0, 0, 1, 0, 0, 1, 0, 0, 0, 0, 0, 1,
         ))
     }
+
+    /// [iSWAP](https://en.wikipedia.org/wiki/Quantum_logic_gate#Imaginary_swap_(iSWAP)),
+    /// which swaps the qubits and puts a phase of `i` on `|01⟩` and `|10⟩`
+    pub fn iswap() -> BinaryGate {
+        let mut mat = swap().mat;
+        mat[(1, 2)] = Complex::i();
+        mat[(2, 1)] = Complex::i();
+        BinaryGate::new(mat)
+    }
+
+    /// [√SWAP](https://en.wikipedia.org/wiki/Quantum_logic_gate#Square_root_of_swap_gate_(%E2%88%9ASWAP)),
+    /// which swaps the qubits when run twice
+    pub fn sqrt_swap() -> BinaryGate {
+        let mut mat = Matrix::identity();
+        let (plus, minus) =
+            (Complex::new(0.5, 0.5), Complex::new(0.5, -0.5));
+        mat[(1, 1)] = plus;
+        mat[(1, 2)] = minus;
+        mat[(2, 1)] = minus;
+        mat[(2, 2)] = plus;
+        BinaryGate::new(mat)
+    }
+
+    /// The controlled Z gate, which flips the phase of `|11⟩`. It's
+    /// symmetric, so either qubit can be the control.
+    pub fn cz() -> BinaryGate {
+        controlled(&unitary::gates::z())
+    }
+
+    /// The controlled phase gate `diag(1, 1, 1, e^{iλ})`, a building
+    /// block of the quantum Fourier transform. Like [`cz`], which is
+    /// `cphase(π)`, it's symmetric.
+    pub fn cphase(lambda: Float) -> BinaryGate {
+        controlled(&unitary::gates::p(lambda))
+    }
+
+    /// Run `gate` on the low-order qubit when the high-order one is `|1⟩`
+    pub fn controlled(gate: &UnaryGate) -> BinaryGate {
+        let mut mat = Matrix::identity();
+        mat.fixed_slice_mut::<U2, U2>(2, 2).copy_from(&gate.mat);
+        BinaryGate::new(mat)
+    }
+
+    /// CNOT with the low-order qubit as the control, flipping the
+    /// high-order one
+    pub fn cnot_reversed() -> BinaryGate {
+        cnot().swap()
+    }
+
+    /// Run `gate` on the high-order qubit when the low-order one is `|1⟩`
+    pub fn controlled_reversed(gate: &UnaryGate) -> BinaryGate {
+        controlled(gate).swap()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use approx::assert_relative_eq;
+    #[test]
+    fn cnot_clones_only_orthogonal_states() {
+        let cnot = gates::cnot();
+        let basis = [Qubit::zero(), Qubit::one()];
+        assert_relative_eq!(
+            best_cloning_fidelity(&cnot, &basis),
+            1.0
+        );
+        // |+⟩ becomes a Bell state, leaving each copy maximally mixed
+        let zero_plus = [Qubit::zero(), Qubit::plus()];
+        assert_relative_eq!(
+            best_cloning_fidelity(&cnot, &zero_plus),
+            0.5,
+            epsilon = 1e-6
+        );
+        // Swapping moves the state rather than copying it
+        assert_relative_eq!(
+            best_cloning_fidelity(&gates::swap(), &zero_plus),
+            0.5,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn no_gate_beats_universal_cloning_bound() {
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let mut rng = SmallRng::seed_from_u64(5);
+        let states: Vec<_> = (0..8)
+            .flat_map(|i| {
+                (0..8).map(move |j| {
+                    let theta = crate::consts::PI * i as Float / 7.0;
+                    let phi = crate::consts::PI * j as Float / 4.0;
+                    Qubit::from_theta_phi(theta, phi)
+                })
+            })
+            .collect();
+        let mut gates = vec![gates::cnot(), gates::swap()];
+        gates.extend((0..200).map(|_| BinaryGate::random(&mut rng)));
+        for gate in &gates {
+            assert!(
+                best_cloning_fidelity(gate, &states) <= 5.0 / 6.0
+            );
+        }
+    }
+
+    #[test]
+    fn controlled_gates() {
+        use crate::gates::unitary::gates::{not, z};
+        assert_relative_eq!(
+            gates::controlled(&not()).mat,
+            gates::cnot().mat
+        );
+        assert_relative_eq!(
+            gates::controlled(&z()).mat,
+            gates::cz().mat
+        );
+        let mut diag = Matrix::identity();
+        diag[(3, 3)] = -Complex::one();
+        assert_eq!(gates::cz().mat, diag);
+        assert_eq!(gates::cz().swap(), gates::cz());
+        assert_eq!(gates::cz().to_string(), "CZ");
+    }
+
+    #[test]
+    fn matrix() {
+        let swap = gates::swap();
+        assert_eq!(swap.matrix(), &swap.mat);
+        assert_eq!(swap.matrix()[(1, 2)], Complex::one());
+        assert_eq!(swap.matrix()[(1, 1)], Complex::zero());
+    }
+
+    #[test]
+    fn reversed_controls() {
+        use crate::gates::unitary::gates::{not, z};
+        let cnot_reversed = gates::cnot_reversed();
+        for &(input, output) in
+            &[(0b00, 0b00), (0b01, 0b11), (0b10, 0b10), (0b11, 0b01)]
+        {
+            assert_relative_eq!(
+                (&cnot_reversed * Register2::basis(input))
+                    .into_vector(),
+                Register2::basis(output).into_vector()
+            );
+        }
+        assert_eq!(gates::controlled_reversed(&not()), cnot_reversed);
+        // A controlled phase doesn't care which qubit is the control
+        assert_eq!(gates::controlled_reversed(&z()), gates::cz());
+    }
+
+    #[test]
+    fn cphase() {
+        use crate::consts::PI;
+        assert_relative_eq!(
+            gates::cphase(PI).mat,
+            gates::cz().mat,
+            epsilon = 1e-6
+        );
+        assert_eq!(gates::cphase(0.0), BinaryGate::identity());
+        for &(lambda, mu) in &[(0.3, 1.1), (-2.0, 0.7), (PI, PI)] {
+            assert_relative_eq!(
+                gates::cphase(lambda).compose(&gates::cphase(mu)).mat,
+                gates::cphase(lambda + mu).mat,
+                epsilon = 1e-6
+            );
+        }
+        for i in 0..20 {
+            let lambda = i as Float * 0.77 - 7.0;
+            let gate = gates::cphase(lambda);
+            assert!(is_unitary(&gate.mat));
+            assert_relative_eq!(gate.swap().mat, gate.mat);
+            assert_relative_eq!(
+                gate.mat[(3, 3)],
+                Complex::exp_ix(lambda),
+                epsilon = 1e-6
+            );
+        }
+    }
+
+    #[test]
+    fn swap_variants() {
+        use crate::gates::unitary::gates::z;
+        let sqrt_swap = gates::sqrt_swap();
+        assert_relative_eq!(
+            sqrt_swap.compose(&sqrt_swap).mat,
+            gates::swap().mat
+        );
+        let iswap = gates::iswap();
+        assert_relative_eq!(
+            iswap.compose(&iswap).mat,
+            z().tensor(&z()).mat
+        );
+        let reg = QuantumRegister::<U4>::basis(0b01);
+        assert_relative_eq!(
+            iswap.apply(reg).into_vector(),
+            QuantumRegister::<U4>::basis(0b10).into_vector()
+                * Complex::i()
+        );
+    }
+
+    #[test]
+    fn dagger() {
+        for gate in &[gates::cnot(), gates::swap()] {
+            assert_eq!(&gate.dagger(), gate);
+        }
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let gate =
+            BinaryGate::random(&mut SmallRng::seed_from_u64(3));
+        assert_ne!(gate.dagger(), gate);
+        assert_relative_eq!(
+            gate.compose(&gate.dagger()).mat,
+            Matrix::identity(),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn try_new() {
+        assert_eq!(
+            BinaryGate::try_new(gates::cnot().mat),
+            Ok(gates::cnot())
+        );
+        assert!(is_unitary(&gates::iswap().mat));
+        let mut mat = gates::swap().mat;
+        mat[(0, 0)] = Complex::zero();
+        assert!(!is_unitary(&mat));
+        assert_eq!(BinaryGate::try_new(mat), Err(NotUnitary));
+    }
+
+    #[test]
+    fn pow() {
+        let cnot = gates::cnot();
+        assert_eq!(cnot.pow(0), BinaryGate::identity());
+        assert_eq!(cnot.pow(1), cnot);
+        assert_eq!(cnot.pow(2), BinaryGate::identity());
+        assert_eq!(cnot.pow(7), cnot);
+        let sqrt_swap = gates::sqrt_swap();
+        assert_relative_eq!(
+            sqrt_swap.pow(4).mat,
+            Matrix::identity(),
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            gates::iswap().pow(3).mat,
+            gates::iswap().dagger().mat,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn large_powers_stay_unitary() {
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let gate =
+            BinaryGate::random(&mut SmallRng::seed_from_u64(6));
+        for &n in &[1 << 16, (1 << 20) + 3, u32::MAX] {
+            assert!(is_unitary(&gate.pow(n).mat));
+        }
+        let unary =
+            UnaryGate::random(&mut SmallRng::seed_from_u64(6));
+        let controlled =
+            BinaryGate::controlled_power(&unary, 1 << 30);
+        assert!(is_unitary(&controlled.mat));
+    }
+
+    #[test]
+    fn controlled_power() {
+        use crate::gates::unitary::gates::{pauli::z, s, t};
+        let cs2 = BinaryGate::controlled_power(&s(), 2);
+        assert_relative_eq!(
+            cs2.mat,
+            gates::controlled(&s().pow(2)).mat,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(cs2.mat, gates::cz().mat, epsilon = 1e-6);
+        assert_eq!(
+            BinaryGate::controlled_power(&z(), 2),
+            BinaryGate::identity()
+        );
+        assert_eq!(gates::cz().pow(2), BinaryGate::identity());
+        assert_eq!(
+            BinaryGate::controlled_power(&z(), 0),
+            BinaryGate::identity()
+        );
+        assert_relative_eq!(
+            BinaryGate::controlled_power(&t(), 4).mat,
+            gates::cz().mat,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn phase_oracle() {
+        let oracle = BinaryGate::phase_oracle(|x| x & 1 == 1);
+        let mut diag = Matrix::identity();
+        diag[(1, 1)] = -Complex::one();
+        diag[(3, 3)] = -Complex::one();
+        assert_eq!(oracle.mat, diag);
+        assert_eq!(
+            BinaryGate::phase_oracle(|_| false),
+            BinaryGate::identity()
+        );
+        assert_eq!(
+            BinaryGate::phase_oracle(|_| true).mat,
+            -Matrix::identity()
+        );
+    }
+
+    #[test]
+    fn deutsch_jozsa() {
+        use crate::gates::unitary::gates::h;
+        let hh = h().tensor(&h());
+        // |00⟩ comes back for constant functions, and never for balanced
+        // ones
+        let p_zero = |f: &dyn Fn(usize) -> bool| {
+            let oracle = BinaryGate::phase_oracle(f);
+            let reg = hh.compose(&oracle).compose(&hh).apply(
+                Register2::from_2_qubits(
+                    Qubit::zero(),
+                    Qubit::zero(),
+                ),
+            );
+            reg.probability(0b00)
+        };
+        for constant in &[false, true] {
+            assert_relative_eq!(
+                p_zero(&|_| *constant),
+                1.0,
+                epsilon = 1e-6
+            );
+        }
+        let balanced: [&dyn Fn(usize) -> bool; 3] =
+            [&|x| x & 1 == 1, &|x| x >= 2, &|x| x == 1 || x == 2];
+        for f in &balanced {
+            assert_relative_eq!(p_zero(f), 0.0, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn embedded_cnot() {
+        use nalgebra::{DVector, U8};
+        let cnot = gates::cnot().embed(3, (0, 1));
+        assert_eq!(cnot.shape(), (8, 8));
+        let ket_100 =
+            QuantumRegister::<U8>::basis(0b100).into_vector();
+        let out =
+            &cnot * DVector::from_column_slice(ket_100.as_slice());
+        assert_eq!(
+            out.as_slice(),
+            QuantumRegister::<U8>::basis(0b110)
+                .into_vector()
+                .as_slice()
+        );
+        // Agrees with apply_to on every basis state, in both positions
+        for &(first, second) in &[(0, 1), (1, 2)] {
+            let mat = gates::iswap().embed(3, (first, second));
+            for state in 0..8 {
+                let reg = QuantumRegister::<U8>::basis(state);
+                let expected = gates::iswap()
+                    .apply_to(reg, first, second)
+                    .into_vector();
+                assert_relative_eq!(
+                    mat.column(state).into_owned(),
+                    DVector::from_column_slice(expected.as_slice())
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Embedded gates need adjacent qubits")]
+    fn embed_needs_adjacent_qubits() {
+        gates::cnot().embed(3, (0, 2));
+    }
+
+    #[test]
+    fn apply_many_matches_apply() {
+        use crate::gates::unitary::gates::h;
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let mut rng = SmallRng::seed_from_u64(2);
+        let regs: Vec<_> = (0..500)
+            .map(|_| {
+                Register2::from_2_qubits(
+                    h().run(Qubit::random(&mut rng)),
+                    Qubit::random(&mut rng),
+                )
+            })
+            .collect();
+        let cnot = gates::cnot();
+        let serial: Vec<_> =
+            regs.iter().map(|reg| cnot.apply(reg.clone())).collect();
+        assert_eq!(cnot.apply_many(&regs), serial);
+        assert!(cnot.apply_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn operator_form() {
+        use crate::gates::unitary::gates::h;
+        let cnot = gates::cnot();
+        for &(a, b) in &[(0u8, 0u8), (0, 1), (1, 0), (1, 1)] {
+            let qubit = Qubit::from;
+            let reg = || {
+                Register2::from_2_qubits(&h() * qubit(a), qubit(b))
+            };
+            assert_eq!(&cnot * reg(), cnot.apply(reg()));
+            assert_eq!(
+                gates::swap() * reg(),
+                gates::swap().apply(reg())
+            );
+        }
+        let (swap, cz) = (gates::swap(), gates::cz());
+        assert_eq!(&cnot * &cnot, BinaryGate::identity());
+        assert_eq!(&swap * &cnot, swap.compose(&cnot));
+        assert_ne!(&swap * &cnot, &cnot * &swap);
+        assert_relative_eq!(
+            (&cz * &swap).mat,
+            cz.mat * swap.mat,
+            epsilon = 1e-6
+        );
+        let bell = &cnot
+            * Register2::from_2_qubits(Qubit::plus(), Qubit::zero());
+        assert_relative_eq!(
+            bell.probabilities()[0b00],
+            0.5,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            bell.probabilities()[0b11],
+            0.5,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn from_layers_matches_bell_example() {
+        use crate::gates::unitary::gates::h;
+        let identity = UnaryGate::identity();
+        let entangle = gates::cnot()
+            .compose(&BinaryGate::from_layers(&h(), &identity));
+        for &(a, b) in &[(0u8, 0u8), (0, 1), (1, 0), (1, 1)] {
+            let qubit = Qubit::from;
+            // As in examples/bell.rs
+            let merged =
+                Register2::from_2_qubits(h().run(qubit(a)), qubit(b));
+            let expected = gates::cnot().apply(merged);
+            let got = entangle
+                .apply(Register2::from_2_qubits(qubit(a), qubit(b)));
+            assert_relative_eq!(
+                got.into_vector(),
+                expected.into_vector(),
+                epsilon = 1e-6
+            );
+        }
+    }
+
+    #[test]
+    fn identity_does_nothing() {
+        let identity = BinaryGate::identity();
+        let reg = QuantumRegister::<U4>::ghz();
+        assert_eq!(identity.apply(reg.clone()), reg);
+        for g in &[gates::cnot(), gates::iswap()] {
+            assert_eq!(&identity.compose(g), g);
+            assert_eq!(&g.compose(&identity), g);
+        }
+    }
+
+    #[test]
+    fn inverse() {
+        for gate in &[gates::cnot(), gates::swap()] {
+            assert_eq!(&gate.inverse(), gate);
+        }
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let mut rng = SmallRng::seed_from_u64(9);
+        let mut gates = vec![gates::iswap(), gates::sqrt_swap()];
+        gates.extend((0..10).map(|_| BinaryGate::random(&mut rng)));
+        for gate in &gates {
+            assert_relative_eq!(
+                gate.compose(&gate.inverse()).mat,
+                Matrix::identity(),
+                epsilon = 1e-6
+            );
+        }
+    }
+
     #[test]
     fn cnot_propertys() {
         let x = gates::cnot();
@@ -102,6 +947,166 @@ mod tests {
         )
     }
 
+    #[test]
+    fn trace_and_determinant() {
+        assert_eq!(gates::cnot().trace(), Complex::from_re(2.0));
+        assert_eq!(gates::cnot().determinant(), -Complex::one());
+        assert_eq!(gates::swap().trace(), Complex::from_re(2.0));
+        assert_eq!(gates::swap().determinant(), -Complex::one());
+    }
+
+    #[test]
+    fn process_fidelity() {
+        let cnot = gates::cnot();
+        assert_relative_eq!(cnot.process_fidelity(&cnot), 1.0);
+        let minus_cnot = BinaryGate::new(-cnot.mat);
+        assert_relative_eq!(cnot.process_fidelity(&minus_cnot), 1.0);
+        // CNOT and SWAP only agree on |00>
+        assert_relative_eq!(
+            cnot.process_fidelity(&gates::swap()),
+            1.0 / 16.0
+        );
+    }
+
+    #[test]
+    fn apply_to_non_adjacent() {
+        use crate::gates::unitary::gates::h;
+        use nalgebra::U8;
+        let reg = QuantumRegister::<U8>::from_classical(0b000.into())
+            .apply_unary(&h(), 0);
+        let reg = gates::cnot().apply_to(reg, 0, 2);
+        let expected = QuantumRegister::<U8>::amplitude_encode(&[
+            1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0,
+        ])
+        .unwrap();
+        assert_relative_eq!(
+            reg.clone().into_vector(),
+            expected.into_vector(),
+            epsilon = 1e-6
+        );
+        // Qubits 0 and 2 are entangled, qubit 1 is left alone
+        assert!(reg.is_separable_across(&[1], 1e-6));
+        assert!(!reg.is_separable_across(&[0], 1e-6));
+
+        // Control below the target
+        let reg = QuantumRegister::<U8>::from_classical(0b001.into());
+        assert_eq!(
+            gates::cnot().apply_to(reg, 2, 0),
+            QuantumRegister::from_classical(0b101.into())
+        );
+    }
+
+    #[test]
+    fn cnot_on_every_qubit_pair() {
+        use nalgebra::U8;
+        let reg = QuantumRegister::<U8>::basis(0b100);
+        assert_eq!(
+            gates::cnot().apply_to(reg, 0, 2),
+            QuantumRegister::basis(0b101)
+        );
+        for control in 0..3 {
+            for target in (0..3).filter(|&t| t != control) {
+                for state in 0..8 {
+                    let control_bit = 1 << (2 - control);
+                    let target_bit = 1 << (2 - target);
+                    let expected = if state & control_bit == 0 {
+                        state
+                    } else {
+                        state ^ target_bit
+                    };
+                    assert_eq!(
+                        gates::cnot().apply_to(
+                            QuantumRegister::<U8>::basis(state),
+                            control,
+                            target
+                        ),
+                        QuantumRegister::basis(expected)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn apply_to_adjacent_matches_apply() {
+        use crate::qubit::Qubit;
+        let reg = QuantumRegister::from_2_qubits(
+            Qubit::from_theta_phi(0.4, 1.3),
+            Qubit::from_theta_phi(2.1, 0.2),
+        );
+        let cnot = gates::cnot();
+        assert_relative_eq!(
+            cnot.apply_to(reg.clone(), 0, 1).into_vector(),
+            cnot.apply(reg.clone()).into_vector(),
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            cnot.apply_to(reg.clone(), 1, 0).into_vector(),
+            cnot.swap().apply(reg).into_vector(),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(gates::cnot().to_string(), "CNOT");
+        assert_eq!(gates::swap().to_string(), "SWAP");
+        let phase = BinaryGate::new(Matrix::from_diagonal(
+            &nalgebra::Vector4::new(
+                Complex::one(),
+                Complex::one(),
+                Complex::one(),
+                Complex::i(),
+            ),
+        ));
+        assert_eq!(
+            phase.to_string(),
+            "[1, 0, 0, 0]\n\
+             [0, 1, 0, 0]\n\
+             [0, 0, 1, 0]\n\
+             [0, 0, 0, 1i]"
+        );
+        // Precision carries through to each entry
+        assert!(format!("{:.2}", phase).ends_with("0.00, 1.00i]"));
+        // A random gate has no name, so it shows its matrix
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let random =
+            BinaryGate::random(&mut SmallRng::seed_from_u64(3));
+        assert_eq!(random.name(), None);
+        let shown = random.to_string();
+        let rows: Vec<_> = shown.lines().collect();
+        assert_eq!(rows.len(), 4);
+        for row in rows {
+            assert!(
+                row.starts_with('[') && row.ends_with(']'),
+                "{}",
+                row
+            );
+            assert_eq!(row.matches(", ").count(), 3, "{}", row);
+        }
+    }
+
+    #[test]
+    fn random_gates_are_spread_out() {
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let mut rng = SmallRng::seed_from_u64(42);
+        let identity = BinaryGate::identity();
+        let n = 1000;
+        let total: Float = (0..n)
+            .map(|_| BinaryGate::random(&mut rng))
+            .map(|gate| gate.process_fidelity(&identity))
+            .sum();
+        // The expected fidelity of a Haar-random gate is 1/16
+        let mean = total / n as Float;
+        assert!(
+            mean < 0.1,
+            "Mean fidelity with identity was {}",
+            mean
+        );
+    }
+
     #[test]
     fn swap_propertys() {
         let x = gates::swap().mat;
@@ -0,0 +1,171 @@
+//! Gates on any number of qubits
+
+use crate::complex::Complex;
+use crate::gates::unitary::UnaryGate;
+use crate::registers::quantum::QuantumRegister;
+use crate::Float;
+
+use approx::assert_relative_eq;
+use nalgebra::allocator::Allocator;
+use nalgebra::default_allocator::DefaultAllocator;
+use nalgebra::dimension::DimName;
+use nalgebra::{MatrixN, U2};
+
+/// A gate on a register with `N` states
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gate<N: DimName>
+where
+    DefaultAllocator: Allocator<Complex, N, N>,
+{
+    pub(crate) mat: MatrixN<Complex, N>,
+}
+
+impl<N: DimName> Gate<N>
+where
+    DefaultAllocator:
+        Allocator<Complex, N, N> + Allocator<Complex, N>,
+{
+    /// Create a gate.
+    ///
+    /// Panics if mat is not [Unitary](https://en.wikipedia.org/wiki/Unitary_matrix)
+    pub fn new(mat: MatrixN<Complex, N>) -> Self {
+        let x = &mat * mat.transpose().map(|x| x.conj());
+        assert_relative_eq!(
+            x,
+            MatrixN::<Complex, N>::identity(),
+            epsilon = 1.0e-6
+        );
+        Self { mat }
+    }
+
+    /// Runs the gate on a register.
+    pub fn apply(
+        &self,
+        reg: QuantumRegister<N>,
+    ) -> QuantumRegister<N> {
+        QuantumRegister::from_vector_unchecked(
+            &self.mat * reg.into_vector(),
+        )
+    }
+
+    /// The [quantum Fourier transform](https://en.wikipedia.org/wiki/Quantum_Fourier_transform)
+    /// as a gate, the matrix form of
+    /// [`QuantumRegister::qft`](QuantumRegister::qft)
+    pub fn qft() -> Self {
+        let dim = N::dim();
+        let scale = Complex::from_re((dim as Float).sqrt().recip());
+        Self::new(MatrixN::<Complex, N>::from_fn(|k, j| {
+            let turns = ((j * k) % dim) as Float / dim as Float;
+            Complex::exp_ix(2.0 * crate::consts::PI * turns) * scale
+        }))
+    }
+
+    pub fn matrix(&self) -> &MatrixN<Complex, N> {
+        &self.mat
+    }
+
+    /// The gate that runs `other`, then `self`
+    pub fn compose(&self, other: &Self) -> Self {
+        Self::new(&self.mat * &other.mat)
+    }
+
+    /// The inverse gate, the conjugate transpose
+    pub fn dagger(&self) -> Self {
+        Self {
+            mat: self.mat.transpose().map(|x| x.conj()),
+        }
+    }
+}
+
+/// Run `gate` on the lowest-order qubit when the `num_controls` qubits
+/// above it are all `|1⟩`. The matrix is the identity, except for
+/// `gate` in the bottom right corner.
+///
+/// Panics unless `N` is `2^(num_controls + 1)`.
+pub fn multi_controlled<N: DimName>(
+    gate: &UnaryGate,
+    num_controls: usize,
+) -> Gate<N>
+where
+    DefaultAllocator:
+        Allocator<Complex, N, N> + Allocator<Complex, N>,
+{
+    assert_eq!(
+        N::dim(),
+        2 << num_controls,
+        "A gate with {} controls acts on {} states",
+        num_controls,
+        2 << num_controls
+    );
+    let corner = N::dim() - 2;
+    let mut mat = MatrixN::<Complex, N>::identity();
+    mat.fixed_slice_mut::<U2, U2>(corner, corner)
+        .copy_from(&gate.mat);
+    Gate::new(mat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gates::binary::gates::cnot;
+    use crate::gates::ternary::gates::{ccz, toffoli};
+    use crate::gates::unitary::gates::{not, z};
+    use nalgebra::{U16, U4, U8};
+
+    #[test]
+    fn qft() {
+        use approx::assert_relative_eq;
+        let qft = Gate::<U8>::qft();
+        let identity = MatrixN::<Complex, U8>::identity();
+        assert_relative_eq!(
+            qft.compose(&qft.dagger()).mat,
+            identity,
+            epsilon = 1e-6
+        );
+        for state in 0..8 {
+            let reg = QuantumRegister::<U8>::basis(state);
+            assert_relative_eq!(
+                qft.apply(reg.clone()).into_vector(),
+                reg.qft().into_vector(),
+                epsilon = 1e-6
+            );
+        }
+        let reg = QuantumRegister::<U8>::w_state();
+        assert_relative_eq!(
+            qft.dagger().apply(qft.apply(reg.clone())).into_vector(),
+            reg.into_vector(),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn multi_controlled_not() {
+        assert_eq!(multi_controlled::<U4>(&not(), 1).mat, cnot().mat);
+        assert_eq!(multi_controlled::<U8>(&not(), 2), toffoli());
+        assert_eq!(multi_controlled::<U8>(&z(), 2), ccz());
+        let flips = multi_controlled::<U16>(&not(), 3);
+        for state in 0..16 {
+            let reg = QuantumRegister::<U16>::basis(state);
+            let expected = if state >> 1 == 0b111 {
+                state ^ 1
+            } else {
+                state
+            };
+            assert_eq!(
+                flips.apply(reg),
+                QuantumRegister::basis(expected)
+            );
+        }
+        // Doing it twice undoes it
+        let twice = flips.compose(&flips);
+        assert_eq!(twice.mat, MatrixN::<Complex, U16>::identity());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "A gate with 2 controls acts on 8 states"
+    )]
+    fn multi_controlled_checks_size() {
+        multi_controlled::<U4>(&not(), 2);
+    }
+}
@@ -1,3 +1,63 @@
 //! Quantum gates
+use std::error::Error;
+use std::fmt;
+
+/// `AbsDiffEq` and `RelativeEq` for a gate, comparing the matrices in
+/// its `mat` field
+macro_rules! impl_approx_eq {
+    ($gate:ty, $mat:ty) => {
+        impl approx::AbsDiffEq for $gate {
+            type Epsilon = <$mat as approx::AbsDiffEq>::Epsilon;
+            fn default_epsilon() -> Self::Epsilon {
+                <$mat as approx::AbsDiffEq>::default_epsilon()
+            }
+            fn abs_diff_eq(
+                &self,
+                other: &Self,
+                epsilon: Self::Epsilon,
+            ) -> bool {
+                <$mat as approx::AbsDiffEq>::abs_diff_eq(
+                    &self.mat, &other.mat, epsilon,
+                )
+            }
+        }
+
+        impl approx::RelativeEq for $gate {
+            fn default_max_relative() -> Self::Epsilon {
+                <$mat as approx::RelativeEq>::default_max_relative()
+            }
+
+            fn relative_eq(
+                &self,
+                other: &Self,
+                epsilon: Self::Epsilon,
+                max_relative: Self::Epsilon,
+            ) -> bool {
+                <$mat as approx::RelativeEq>::relative_eq(
+                    &self.mat,
+                    &other.mat,
+                    epsilon,
+                    max_relative,
+                )
+            }
+        }
+    };
+}
+
 pub mod binary;
+pub mod general;
+pub mod pauli;
+pub mod ternary;
 pub mod unitary;
+
+/// A gate matrix that isn't [unitary](https://en.wikipedia.org/wiki/Unitary_matrix)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotUnitary;
+
+impl fmt::Display for NotUnitary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "gate matrix isn't unitary")
+    }
+}
+
+impl Error for NotUnitary {}
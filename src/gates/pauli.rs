@@ -0,0 +1,97 @@
+//! [Pauli operators](https://en.wikipedia.org/wiki/Pauli_matrices), and
+//! tensor products of them for building observables
+use crate::complex::Complex;
+use crate::gates::binary::BinaryGate;
+use crate::gates::unitary::{gates, UnaryGate};
+use crate::registers::kron_mat;
+
+use nalgebra::Matrix2;
+
+/// A single qubit Pauli operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pauli {
+    I,
+    X,
+    Y,
+    Z,
+}
+
+impl Pauli {
+    pub fn matrix(&self) -> Matrix2<Complex> {
+        self.gate().mat
+    }
+
+    /// The operator as a gate
+    pub fn gate(&self) -> UnaryGate {
+        match self {
+            Pauli::I => UnaryGate::identity(),
+            Pauli::X => gates::pauli::x(),
+            Pauli::Y => gates::pauli::y(),
+            Pauli::Z => gates::pauli::z(),
+        }
+    }
+}
+
+/// The two qubit observable `ops[0] ⊗ ops[1]`, where `ops[0]` acts on the
+/// high-order qubit. Like every Pauli string, it's both unitary and
+/// hermitian.
+///
+/// Panics unless there are exactly two operators.
+pub fn pauli_string(ops: &[Pauli]) -> BinaryGate {
+    assert_eq!(
+        ops.len(),
+        2,
+        "A two qubit Pauli string needs 2 operators, not {}",
+        ops.len()
+    );
+    BinaryGate::new(kron_mat(&ops[0].matrix(), &ops[1].matrix()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gates::binary::is_unitary;
+    use approx::assert_relative_eq;
+    use nalgebra::Matrix4;
+
+    #[test]
+    fn zz_is_diagonal() {
+        let zz = pauli_string(&[Pauli::Z, Pauli::Z]);
+        let diag = [1.0, -1.0, -1.0, 1.0];
+        let expected =
+            Matrix4::from_fn(
+                |r, c| if r == c { diag[r] } else { 0.0 },
+            )
+            .map(Complex::from_re);
+        assert_eq!(zz.mat, expected);
+    }
+
+    #[test]
+    fn strings_are_unitary_and_hermitian() {
+        let all = [Pauli::I, Pauli::X, Pauli::Y, Pauli::Z];
+        for &a in &all {
+            for &b in &all {
+                let mat = pauli_string(&[a, b]).mat;
+                assert!(is_unitary(&mat));
+                assert_eq!(mat.transpose().map(|x| x.conj()), mat);
+            }
+        }
+        assert_eq!(
+            pauli_string(&[Pauli::X, Pauli::I]),
+            Pauli::X.gate().tensor(&UnaryGate::identity())
+        );
+        // Every Pauli string squares to the identity
+        let xz = pauli_string(&[Pauli::X, Pauli::Z]);
+        assert_relative_eq!(
+            xz.mat * xz.mat,
+            Matrix4::identity(),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "needs 2 operators")]
+    fn wrong_length_panics() {
+        pauli_string(&[Pauli::Z]);
+    }
+}
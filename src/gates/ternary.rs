@@ -0,0 +1,150 @@
+//! Gates that act on three qubits
+
+use crate::complex::Complex;
+
+use super::general::Gate;
+
+use nalgebra::{MatrixN, U8};
+
+/// A gate on three qubits
+pub type TernaryGate = Gate<U8>;
+
+/// The gate that maps the basis state `|c⟩` to `|f(c)⟩`
+fn permutation(f: impl Fn(usize) -> usize) -> TernaryGate {
+    Gate::new(MatrixN::<Complex, U8>::from_fn(|r, c| {
+        if f(c) == r {
+            Complex::one()
+        } else {
+            Complex::zero()
+        }
+    }))
+}
+
+pub mod gates {
+    use super::*;
+
+    /// The [Toffoli / CCNOT](https://en.wikipedia.org/wiki/Toffoli_gate)
+    /// gate. Flips the third qubit if the first two are both `|1⟩`.
+    pub fn toffoli() -> TernaryGate {
+        permutation(|c| if c >> 1 == 0b11 { c ^ 1 } else { c })
+    }
+
+    /// The [Fredkin / CSWAP](https://en.wikipedia.org/wiki/Fredkin_gate)
+    /// gate. Swaps the second and third qubits if the first is `|1⟩`.
+    pub fn fredkin() -> TernaryGate {
+        permutation(|c| match c {
+            0b101 => 0b110,
+            0b110 => 0b101,
+            c => c,
+        })
+    }
+
+    /// The controlled-controlled-Z gate. Flips the phase of `|111⟩`.
+    pub fn ccz() -> TernaryGate {
+        Gate::new(MatrixN::<Complex, U8>::from_fn(|r, c| {
+            match (r == c, r) {
+                (false, _) => Complex::zero(),
+                (true, 0b111) => -Complex::one(),
+                (true, _) => Complex::one(),
+            }
+        }))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::gates::unitary::gates::h;
+        use crate::registers::quantum::QuantumRegister;
+        use approx::assert_relative_eq;
+
+        fn basis_states() -> impl Iterator<Item = QuantumRegister<U8>>
+        {
+            (0..8u64)
+                .map(|i| QuantumRegister::from_classical(i.into()))
+        }
+
+        #[test]
+        fn toffoli_truth_table() {
+            let toffoli = toffoli();
+            for (state, reg) in basis_states().enumerate() {
+                let expected = if state >> 1 == 0b11 {
+                    state ^ 1
+                } else {
+                    state
+                };
+                assert_eq!(
+                    toffoli.apply(reg),
+                    QuantumRegister::basis(expected),
+                    "|{:03b}⟩",
+                    state
+                );
+            }
+        }
+
+        #[test]
+        fn fredkin_truth_table() {
+            let fredkin = fredkin();
+            for (state, reg) in basis_states().enumerate() {
+                let (control, a, b) =
+                    (state >> 2, state >> 1 & 1, state & 1);
+                let expected = if control == 1 {
+                    0b100 | b << 1 | a
+                } else {
+                    state
+                };
+                assert_eq!(
+                    fredkin.apply(reg),
+                    QuantumRegister::basis(expected),
+                    "|{:03b}⟩",
+                    state
+                );
+            }
+        }
+
+        #[test]
+        fn ccz_is_symmetric() {
+            let ccz = ccz();
+            // Every way of reordering 3 qubits
+            let orders = [
+                [0, 1, 2],
+                [0, 2, 1],
+                [1, 0, 2],
+                [1, 2, 0],
+                [2, 0, 1],
+                [2, 1, 0],
+            ];
+            for order in &orders {
+                let reorder = permutation(|c| {
+                    order.iter().enumerate().fold(
+                        0,
+                        |acc, (i, &q)| {
+                            acc | ((c >> (2 - q) & 1) << (2 - i))
+                        },
+                    )
+                });
+                let undo = Gate::new(
+                    reorder.mat.transpose().map(|x| x.conj()),
+                );
+                assert_relative_eq!(
+                    reorder.compose(&ccz).compose(&undo).mat,
+                    ccz.mat
+                );
+            }
+        }
+
+        #[test]
+        fn ccz_is_toffoli_conjugated_by_h() {
+            let (ccz, toffoli, h) = (ccz(), toffoli(), h());
+            for reg in basis_states() {
+                let expected = reg.apply_unary(&h, 2);
+                let expected =
+                    toffoli.apply(expected).apply_unary(&h, 2);
+                assert_relative_eq!(
+                    ccz.apply(reg).into_vector(),
+                    expected.into_vector(),
+                    epsilon = 1e-6
+                );
+            }
+        }
+    }
+}
@@ -1,21 +1,34 @@
 //! Gates that map a qubit to a qubit
 
-use std::f32::consts::FRAC_1_SQRT_2;
+use crate::consts::FRAC_1_SQRT_2;
 
 use crate::complex::Complex;
+use crate::gates::binary::BinaryGate;
+use crate::gates::NotUnitary;
+use crate::linalg;
+use crate::matrix::Matrix2x2;
 use crate::qubit::Qubit;
+use crate::registers::kron_mat;
+use crate::registers::quantum::QuantumRegister;
+use crate::Float;
 
-use approx::assert_relative_eq;
+use approx::relative_eq;
 use nalgebra;
+use nalgebra::allocator::Allocator;
+use nalgebra::default_allocator::DefaultAllocator;
+use nalgebra::dimension::DimName;
+use rand::Rng;
 
 use num_traits::identities::{one, zero};
 
+use std::ops::Mul;
+
 type Matrix = nalgebra::Matrix2<Complex>;
 
 /// A Unary Gate. Maps a qubit to a qubit
 #[derive(Debug, Clone, PartialEq)]
 pub struct UnaryGate {
-    mat: Matrix,
+    pub(crate) mat: Matrix,
 }
 
 impl UnaryGate {
@@ -23,16 +36,385 @@ impl UnaryGate {
     ///
     /// Panics if mat is not [Unitary](https://en.wikipedia.org/wiki/Unitary_matrix)
     pub fn new(mat: Matrix) -> Self {
-        let x = mat * mat.transpose().map(|x| x.conj());
-        assert_relative_eq!(x, Matrix::identity());
-        Self { mat }
+        match Self::try_new(mat) {
+            Ok(gate) => gate,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Create a unary gate, or `Err` if `mat` is not unitary
+    pub fn try_new(mat: Matrix) -> Result<Self, NotUnitary> {
+        if is_unitary(&mat) {
+            Ok(Self { mat })
+        } else {
+            Err(NotUnitary)
+        }
+    }
+
+    /// The gate that does nothing
+    pub fn identity() -> Self {
+        Self::new(Matrix::identity())
+    }
+
+    /// A [Haar-random](https://en.wikipedia.org/wiki/Haar_measure) gate,
+    /// up to its global phase.
+    pub fn random<R: Rng>(rng: &mut R) -> Self {
+        use crate::consts::PI;
+        // For a Haar-random gate |⟨0|U|0⟩|² = cos²(θ/2) is uniform
+        let theta = 2.0 * rng.gen::<Float>().sqrt().acos();
+        let phi = rng.gen_range(0.0, 2.0 * PI);
+        let lambda = rng.gen_range(0.0, 2.0 * PI);
+        gates::u3(theta, phi, lambda)
     }
+
+    /// Create a unary gate from the crate's own matrix type.
+    ///
+    /// Panics if `m` is not unitary, like [`new`](Self::new)
+    pub fn from_matrix2x2(m: Matrix2x2<Complex>) -> Self {
+        Self::new(m.into())
+    }
+
     /// Takes a qubit and runs the gate on it.
     pub fn run(&self, q: Qubit) -> Qubit {
         Qubit {
             inner: self.mat * q.inner,
         }
     }
+
+    pub fn matrix(&self) -> &Matrix {
+        &self.mat
+    }
+
+    /// The gate that runs `other` then `self`
+    pub fn compose(&self, other: &UnaryGate) -> UnaryGate {
+        UnaryGate::new(self.mat * other.mat)
+    }
+
+    /// The gate run `n` times in a row, by repeated squaring. This is
+    /// the identity for `n == 0`.
+    pub fn pow(&self, mut n: u32) -> Self {
+        let mut result = Matrix::identity();
+        let mut square = self.mat;
+        while n > 0 {
+            if n & 1 == 1 {
+                result *= square;
+                linalg::orthonormalize(&mut result);
+            }
+            square *= square;
+            // A product of unitaries is unitary, but squaring doubles
+            // the rounding error each time, so pull it back to unitary
+            linalg::orthonormalize(&mut square);
+            n >>= 1;
+        }
+        Self::new(result)
+    }
+
+    /// The two qubit gate running `self` on the high-order qubit and
+    /// `other` on the low-order one
+    pub fn tensor(&self, other: &UnaryGate) -> BinaryGate {
+        BinaryGate::new(kron_mat(&self.mat, &other.mat))
+    }
+
+    /// The conjugate transpose, which undoes the gate
+    pub fn dagger(&self) -> Self {
+        Self::new(self.mat.transpose().map(|x| x.conj()))
+    }
+
+    /// The time evolution `e^{-iHt}` of the
+    /// [Hamiltonian](https://en.wikipedia.org/wiki/Hamiltonian_(quantum_mechanics))
+    /// `h` over time `t`.
+    ///
+    /// Writing `h = a·I + |b|·n·σ`, for a unit vector `n` of Pauli
+    /// matrices, its eigenvalues are `a ± |b|`, and the exponential is
+    /// `e^{-iat} (cos(|b|t) I - i sin(|b|t) n·σ)`.
+    ///
+    /// Panics if `h` isn't Hermitian.
+    pub fn from_hamiltonian(h: &Matrix, t: Float) -> UnaryGate {
+        assert!(
+            relative_eq!(
+                *h,
+                h.transpose().map(|x| x.conj()),
+                epsilon = 1.0e-6
+            ),
+            "A Hamiltonian must be Hermitian"
+        );
+        let a = (h[(0, 0)].re() + h[(1, 1)].re()) / 2.0;
+        let traceless = h - Matrix::identity() * Complex::from_re(a);
+        // n·σ squares to the identity, so |b|² is the determinant's
+        // negative
+        let b =
+            (-linalg::determinant(&traceless).re()).max(0.0).sqrt();
+        let phase = Complex::exp_ix(-a * t);
+        let mut mat =
+            Matrix::identity() * Complex::from_re((b * t).cos());
+        if b > 0.0 {
+            let sin = Complex::new(0.0, -(b * t).sin() / b);
+            mat += traceless * sin;
+        }
+        UnaryGate::new(mat * phase)
+    }
+
+    /// The commutator `[self, other] = self·other - other·self`, which is
+    /// zero when the order the gates run in doesn't matter. It isn't
+    /// unitary in general, so it's just a matrix.
+    pub fn commutator(&self, other: &UnaryGate) -> Matrix {
+        self.mat * other.mat - other.mat * self.mat
+    }
+
+    /// Run the gate on qubit `target` of a larger register, leaving the
+    /// other qubits alone.
+    ///
+    /// Qubit 0 is the most significant bit of the basis state, so `not`
+    /// on qubit 0 takes `|00⟩` to `|10⟩`.
+    pub fn apply_to<N: DimName>(
+        &self,
+        reg: QuantumRegister<N>,
+        target: usize,
+    ) -> QuantumRegister<N>
+    where
+        DefaultAllocator: Allocator<Complex, N>,
+    {
+        let mut qubits = reg.into_vector();
+        self.apply_to_slice(qubits.as_mut_slice(), target);
+        QuantumRegister::from_vector_unchecked(qubits)
+    }
+
+    /// Run the gate on qubit `target` of a register's amplitudes.
+    ///
+    /// Qubit 0 is the most significant bit of the basis state index.
+    pub(crate) fn apply_to_slice(
+        &self,
+        amps: &mut [Complex],
+        target: usize,
+    ) {
+        apply_matrix_to_slice(&self.mat, amps, target)
+    }
+
+    /// Decompose the gate into ZYZ Euler angles and a global phase,
+    /// `(β, γ, δ, α)` such that the gate is `e^{iα} Rz(β) Ry(γ) Rz(δ)`.
+    ///
+    /// This is the inverse of [`from_parameters`](Self::from_parameters).
+    pub fn to_parameters(&self) -> (Float, Float, Float, Float) {
+        let phase = linalg::determinant(&self.mat).arg() / 2.0;
+        // Removing the global phase leaves a matrix in SU(2),
+        // [[p, -q*], [q, p*]]
+        let unphase = Complex::exp_ix(-phase);
+        let (p, q) =
+            (self.mat[(0, 0)] * unphase, self.mat[(1, 0)] * unphase);
+        let gamma = 2.0 * q.norm().atan2(p.norm());
+        // When either p or q vanishes only the sum or difference of β
+        // and δ is meaningful, so put it all in β.
+        let (beta, delta) = if q.norm() < 1e-6 {
+            (-2.0 * p.arg(), 0.0)
+        } else if p.norm() < 1e-6 {
+            (2.0 * q.arg(), 0.0)
+        } else {
+            let sum = -2.0 * p.arg();
+            let diff = 2.0 * q.arg();
+            ((sum + diff) / 2.0, (sum - diff) / 2.0)
+        };
+        (beta, gamma, delta, phase)
+    }
+
+    /// The gate `e^{iα} Rz(β) Ry(γ) Rz(δ)` for parameters `(β, γ, δ, α)`.
+    pub fn from_parameters(
+        (beta, gamma, delta, phase): (Float, Float, Float, Float),
+    ) -> UnaryGate {
+        let (cos, sin) = ((gamma / 2.0).cos(), (gamma / 2.0).sin());
+        let sum = Complex::exp_ix((beta + delta) / 2.0);
+        let diff = Complex::exp_ix((beta - delta) / 2.0);
+        let phase = Complex::exp_ix(phase);
+        UnaryGate::new(
+            Matrix::new(
+                sum.conj() * cos,
+                -diff.conj() * sin,
+                diff * sin,
+                sum * cos,
+            )
+            .map(|x| x * phase),
+        )
+    }
+
+    /// Run the gate on `initial` `steps` times, giving the fidelity of the
+    /// state after each step with the exact result.
+    ///
+    /// The exact gate is unitary, but each run rounds to a `Float`, so this
+    /// shows how quickly that rounding error builds up.
+    // The exact result is worked out in f64, which is already a `Float`
+    // with double-precision
+    #[allow(clippy::useless_conversion)]
+    pub fn fidelity_trajectory(
+        &self,
+        initial: &Qubit,
+        steps: usize,
+    ) -> Vec<Float> {
+        // Removing the global phase e^{iα} leaves V ∈ SU(2), and
+        // V = cos(θ/2) I - i sin(θ/2) n·σ, so the k'th power is
+        // e^{ikα} (cos(kθ/2) I + sin(kθ/2)/sin(θ/2) (V - cos(θ/2) I))
+        let alpha =
+            f64::from(linalg::determinant(&self.mat).arg()) / 2.0;
+        let v =
+            self.mat.map(|x| x * Complex::exp_ix(-alpha as Float));
+        let cos_half = f64::from((v[(0, 0)] + v[(1, 1)]).re() / 2.0);
+        let half = cos_half.clamp(-1.0, 1.0).acos();
+        let rotation = v - Matrix::identity()
+            * Complex::from_re(cos_half as Float);
+
+        let mut state = initial.clone();
+        (1..=steps)
+            .map(|k| {
+                state = self.run(state.clone());
+                let k = k as f64;
+                let mut exact = Matrix::identity()
+                    * Complex::from_re((k * half).cos() as Float);
+                // When V = ±I there's no axis of rotation
+                if half.sin().abs() > 1e-9 {
+                    exact += rotation
+                        * Complex::from_re(
+                            ((k * half).sin() / half.sin()) as Float,
+                        );
+                }
+                let phase =
+                    (k * alpha) % (2.0 * std::f64::consts::PI);
+                let exact = Qubit {
+                    inner: exact
+                        * initial.inner
+                        * Complex::exp_ix(phase as Float),
+                };
+                exact.inner_product(&state).mag_square()
+            })
+            .collect()
+    }
+
+    /// Whether the two gates are the same once a global phase is
+    /// factored out, with each entry matching within `epsilon`.
+    pub fn eq_up_to_phase(
+        &self,
+        other: &UnaryGate,
+        epsilon: Float,
+    ) -> bool {
+        linalg::eq_up_to_phase(
+            self.mat.as_slice(),
+            other.mat.as_slice(),
+            epsilon,
+        )
+    }
+
+    /// The eigenvalues, from the roots of the characteristic polynomial
+    /// `λ² - Tr(U) λ + det(U)`. They lie on the unit circle, and are
+    /// equal when the gate is a multiple of the identity.
+    pub fn eigenvalues(&self) -> [Complex; 2] {
+        let half_trace = (self.mat[(0, 0)] + self.mat[(1, 1)]) / 2.0;
+        let det = linalg::determinant(&self.mat);
+        let root = (half_trace * half_trace - det).sqrt();
+        [half_trace + root, half_trace - root]
+    }
+
+    /// The conventional name of the gate, if it's one of the standard
+    /// gates.
+    pub(crate) fn name(&self) -> Option<&'static str> {
+        let known = [
+            ("X", gates::not()),
+            ("Y", gates::pauli::y()),
+            ("Z", gates::z()),
+            ("H", gates::h()),
+            ("S", gates::s()),
+            ("T", gates::t()),
+        ];
+        known
+            .iter()
+            .find(|(_, gate)| relative_eq!(self.mat, gate.mat))
+            .map(|(name, _)| *name)
+    }
+}
+
+/// `&gate * q` is `gate.run(q)`
+impl Mul<Qubit> for &UnaryGate {
+    type Output = Qubit;
+    fn mul(self, q: Qubit) -> Qubit {
+        self.run(q)
+    }
+}
+
+impl Mul<Qubit> for UnaryGate {
+    type Output = Qubit;
+    fn mul(self, q: Qubit) -> Qubit {
+        self.run(q)
+    }
+}
+
+/// `&a * &b` is `a.compose(&b)`, the gate that runs `b` then `a`, as
+/// with their matrices
+impl Mul for &UnaryGate {
+    type Output = UnaryGate;
+    fn mul(self, other: &UnaryGate) -> UnaryGate {
+        self.compose(other)
+    }
+}
+
+/// Multiply the amplitudes of qubit `target` by `mat`, which needn't be
+/// unitary
+pub(crate) fn apply_matrix_to_slice(
+    mat: &Matrix,
+    amps: &mut [Complex],
+    target: usize,
+) {
+    let n_qubits = amps.len().trailing_zeros() as usize;
+    assert!(
+        target < n_qubits,
+        "Target qubit {} out of range for a {} qubit register",
+        target,
+        n_qubits
+    );
+    let bit = 1 << (n_qubits - 1 - target);
+    for i in (0..amps.len()).filter(|i| i & bit == 0) {
+        let (a, b) = (amps[i], amps[i | bit]);
+        amps[i] = mat[(0, 0)] * a + mat[(0, 1)] * b;
+        amps[i | bit] = mat[(1, 0)] * a + mat[(1, 1)] * b;
+    }
+}
+
+impl_approx_eq!(UnaryGate, Matrix);
+
+/// Whether `mat` is [unitary](https://en.wikipedia.org/wiki/Unitary_matrix),
+/// to within `1e-6`, and so can be made into a gate
+pub fn is_unitary(mat: &Matrix) -> bool {
+    linalg::is_unitary(mat)
+}
+
+/// The global phase `start` picks up from running `gates` in order, in
+/// `(-π, π]`.
+///
+/// Panics if the gates don't bring `start` back to itself up to phase.
+pub fn accumulated_phase(
+    gates: &[UnaryGate],
+    start: &Qubit,
+) -> Float {
+    let end = gates
+        .iter()
+        .fold(start.clone(), |qubit, gate| gate.run(qubit));
+    let overlap = start.inner_product(&end);
+    assert!(
+        (overlap.norm() - 1.0).abs() <= 1.0e-5,
+        "The gates don't return the qubit to its start: {:?}",
+        end
+    );
+    overlap.arg()
+}
+
+/// Panics, showing both matrices, unless `a` and `b` are equal up to a
+/// global phase.
+pub fn assert_eq_up_to_phase(
+    a: &UnaryGate,
+    b: &UnaryGate,
+    epsilon: Float,
+) {
+    assert!(
+        a.eq_up_to_phase(b, epsilon),
+        "Gates differ by more than a global phase:{}{}",
+        a.mat,
+        b.mat
+    );
 }
 
 pub mod gates {
@@ -60,6 +442,70 @@ pub mod gates {
         )
     }
 
+    /// Create a [S / phase](https://en.wikipedia.org/wiki/Quantum_logic_gate#Phase_shift_gates) gate, the square root of Z
+    pub fn s() -> UnaryGate {
+        UnaryGate::new(Matrix::new(
+            one(),
+            zero(),
+            zero(),
+            Complex::i(),
+        ))
+    }
+
+    /// The [phase shift](https://en.wikipedia.org/wiki/Quantum_logic_gate#Phase_shift_gates)
+    /// gate `diag(1, e^iλ)`
+    pub fn p(lambda: Float) -> UnaryGate {
+        UnaryGate::new(Matrix::new(
+            one(),
+            zero(),
+            zero(),
+            Complex::exp_ix(lambda),
+        ))
+    }
+
+    /// Create a [T / π/8](https://en.wikipedia.org/wiki/Quantum_logic_gate#Phase_shift_gates) gate, the square root of S
+    pub fn t() -> UnaryGate {
+        UnaryGate::new(Matrix::new(
+            one(),
+            zero(),
+            zero(),
+            Complex::exp_ix(crate::consts::FRAC_PI_4),
+        ))
+    }
+
+    /// Rotate by `theta` about the Bloch sphere's x axis
+    pub fn rx(theta: Float) -> UnaryGate {
+        let cos = Complex::from_re((theta / 2.0).cos());
+        let sin = Complex::new(0.0, -(theta / 2.0).sin());
+        UnaryGate::new(Matrix::new(cos, sin, sin, cos))
+    }
+
+    /// Rotate by `theta` about the Bloch sphere's y axis
+    pub fn ry(theta: Float) -> UnaryGate {
+        let cos = Complex::from_re((theta / 2.0).cos());
+        let sin = Complex::from_re((theta / 2.0).sin());
+        UnaryGate::new(Matrix::new(cos, -sin, sin, cos))
+    }
+
+    /// Rotate by `theta` about the Bloch sphere's z axis
+    pub fn rz(theta: Float) -> UnaryGate {
+        let half = Complex::exp_ix(theta / 2.0);
+        UnaryGate::new(Matrix::new(half.conj(), zero(), zero(), half))
+    }
+
+    /// The general single qubit gate `U(θ, φ, λ)` from
+    /// [OpenQASM](https://arxiv.org/abs/1707.03429), equal to
+    /// `Rz(φ) Ry(θ) Rz(λ)` up to a global phase
+    pub fn u3(theta: Float, phi: Float, lambda: Float) -> UnaryGate {
+        let (cos, sin) = ((theta / 2.0).cos(), (theta / 2.0).sin());
+        UnaryGate::new(Matrix::new(
+            Complex::from_re(cos),
+            -Complex::exp_ix(lambda) * sin,
+            Complex::exp_ix(phi) * sin,
+            Complex::exp_ix(phi + lambda) * cos,
+        ))
+    }
+
     /// Gates from [Pauli matrices](https://en.wikipedia.org/wiki/Pauli_matrices)
     pub mod pauli {
         use super::*;
@@ -85,6 +531,7 @@ pub mod gates {
     #[cfg(test)]
     mod tests {
         use super::*;
+        use approx::assert_relative_eq;
         #[test]
         fn not_ab_is_ba() {
             let not = not();
@@ -128,18 +575,597 @@ pub mod gates {
         }
 
         #[test]
+        fn random_gates_are_unitary() {
+            use rand::rngs::SmallRng;
+            use rand::SeedableRng;
+            let mut rng = SmallRng::seed_from_u64(8);
+            for _ in 0..1000 {
+                let gate = UnaryGate::random(&mut rng);
+                assert!(is_unitary(&gate.mat));
+                let q = gate.run(Qubit::random(&mut rng));
+                assert_relative_eq!(
+                    q.inner
+                        .iter()
+                        .map(|x| x.mag_square())
+                        .sum::<Float>(),
+                    1.0,
+                    epsilon = 1e-5
+                );
+            }
+        }
+
+        #[test]
+        fn from_hamiltonian() {
+            use crate::consts::{FRAC_PI_2, FRAC_PI_4};
+            let evolve = UnaryGate::from_hamiltonian;
+            let z_mat = z().mat;
+            assert_eq_up_to_phase(
+                &evolve(&z_mat, FRAC_PI_4),
+                &s(),
+                1e-6,
+            );
+            assert_eq_up_to_phase(
+                &evolve(&z_mat, FRAC_PI_2),
+                &z(),
+                1e-6,
+            );
+            for &theta in &[0.3, 1.0, -2.2] {
+                assert_relative_eq!(
+                    evolve(&not().mat, theta / 2.0).mat,
+                    rx(theta).mat,
+                    epsilon = 1e-6
+                );
+                assert_relative_eq!(
+                    evolve(&pauli::y().mat, theta / 2.0).mat,
+                    ry(theta).mat,
+                    epsilon = 1e-6
+                );
+            }
+            // A multiple of the identity only adds a global phase
+            let shifted =
+                z_mat + Matrix::identity() * Complex::from_re(3.0);
+            assert_eq_up_to_phase(
+                &evolve(&shifted, 0.7),
+                &evolve(&z_mat, 0.7),
+                1e-6,
+            );
+            assert_eq_up_to_phase(
+                &evolve(&Matrix::identity(), 1.3),
+                &UnaryGate::identity(),
+                1e-6,
+            );
+            // H = (X + Z)/√2 for time π/2 is the Hadamard, up to phase
+            let hadamard =
+                (not().mat + z_mat) * Complex::from_re(FRAC_1_SQRT_2);
+            let gate = evolve(&hadamard, FRAC_PI_2);
+            assert!(is_unitary(&gate.mat));
+            assert_eq_up_to_phase(&gate, &h(), 1e-6);
+        }
+
+        #[test]
+        #[should_panic(expected = "must be Hermitian")]
+        fn hamiltonian_must_be_hermitian() {
+            UnaryGate::from_hamiltonian(&s().mat, 1.0);
+        }
+
+        #[test]
+        fn commutator() {
+            let (x, y, z) = (not(), pauli::y(), z());
+            let two_i = Complex::new(0.0, 2.0);
+            assert_relative_eq!(x.commutator(&y), z.mat * two_i);
+            assert_relative_eq!(y.commutator(&z), x.mat * two_i);
+            assert_relative_eq!(y.commutator(&x), z.mat * -two_i);
+            let zero = Matrix::from_element(Complex::zero());
+            assert_eq!(x.commutator(&x), zero);
+            assert_eq!(z.commutator(&s()), zero);
+        }
+
+        #[test]
+        fn matrix() {
+            let (zero, one) = (Complex::zero(), Complex::one());
+            assert_eq!(
+                not().matrix(),
+                &Matrix::new(zero, one, one, zero)
+            );
+        }
+
+        #[test]
+        fn pow() {
+            assert_eq!(s().pow(0), UnaryGate::identity());
+            assert_eq!(s().pow(1), s());
+            assert_relative_eq!(
+                s().pow(2).mat,
+                z().mat,
+                epsilon = 1e-6
+            );
+            assert_relative_eq!(
+                s().pow(8).mat,
+                Matrix::identity(),
+                epsilon = 1e-6
+            );
+            assert_relative_eq!(
+                t().pow(5).mat,
+                t().pow(4).compose(&t()).mat,
+                epsilon = 1e-6
+            );
+        }
+
+        #[test]
+        fn large_powers_stay_unitary() {
+            use rand::rngs::SmallRng;
+            use rand::SeedableRng;
+            let gate =
+                UnaryGate::random(&mut SmallRng::seed_from_u64(6));
+            for &n in &[1 << 16, (1 << 20) + 3, u32::MAX] {
+                assert!(is_unitary(&gate.pow(n).mat));
+            }
+        }
+
+        #[test]
+        fn eigenvalues() {
+            // In either order
+            let check = |gate: UnaryGate, a: Complex, b: Complex| {
+                let [x, y] = gate.eigenvalues();
+                let close =
+                    |p: Complex, q: Complex| (p - q).norm() < 1e-6;
+                assert!(
+                    (close(x, a) && close(y, b))
+                        || (close(x, b) && close(y, a)),
+                    "Got {} and {}",
+                    x,
+                    y
+                );
+            };
+            let (one, i) = (Complex::one(), Complex::i());
+            check(z(), one, -one);
+            check(s(), one, i);
+            check(h(), one, -one);
+            check(pauli::y(), one, -one);
+            check(
+                t(),
+                one,
+                Complex::exp_ix(crate::consts::FRAC_PI_4),
+            );
+            // Degenerate cases
+            check(UnaryGate::identity(), one, one);
+            let phase = UnaryGate::new(Matrix::identity() * i);
+            check(phase, i, i);
+            for &theta in &[0.3, 2.0, -1.1] {
+                for x in &rx(theta).eigenvalues() {
+                    assert_relative_eq!(
+                        x.norm(),
+                        1.0,
+                        epsilon = 1e-6
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn try_new() {
+            assert_eq!(UnaryGate::try_new(h().mat), Ok(h()));
+            assert!(is_unitary(&s().mat));
+            let mut mat = Matrix::identity();
+            mat[(0, 1)] = Complex::one();
+            assert!(!is_unitary(&mat));
+            assert_eq!(UnaryGate::try_new(mat), Err(NotUnitary));
+            assert_eq!(
+                UnaryGate::try_new(
+                    Matrix::identity() * Complex::from_re(1.1)
+                ),
+                Err(NotUnitary)
+            );
+        }
+
+        #[test]
+        fn from_matrix2x2() {
+            let (one, zero) = (Complex::one(), Complex::zero());
+            let x = Matrix2x2(zero, one, one, zero);
+            assert_eq!(UnaryGate::from_matrix2x2(x), not());
+            let z = Matrix2x2(one, zero, zero, -one);
+            assert_relative_eq!(
+                UnaryGate::from_matrix2x2(
+                    (x + z) * Complex::from_re(FRAC_1_SQRT_2)
+                )
+                .mat,
+                h().mat
+            );
+        }
+
+        #[test]
+        #[should_panic]
+        fn from_nonunitary_matrix2x2() {
+            UnaryGate::from_matrix2x2(
+                Matrix2x2::identity() * Complex::from_re(2.0),
+            );
+        }
+
+        #[test]
+        fn operator_form() {
+            assert_relative_eq!(&h() * Qubit::zero(), &Qubit::plus());
+            assert_relative_eq!(&h() * Qubit::one(), &Qubit::minus());
+            assert_relative_eq!(&h() * Qubit::plus(), &Qubit::zero());
+            assert_eq!(not() * Qubit::zero(), Qubit::one());
+            assert_eq!(&z() * Qubit::one(), -Qubit::one());
+            let (h, s) = (h(), s());
+            assert_relative_eq!(
+                &h * (&s * (&h * Qubit::zero())),
+                &h.run(s.run(h.run(Qubit::zero())))
+            );
+            assert_relative_eq!(
+                (&h * &h).mat,
+                Matrix::identity(),
+                epsilon = 1e-6
+            );
+            assert_eq!(&z() * &h, z().compose(&h));
+            // The right hand gate runs first
+            assert_relative_eq!(
+                &(&s * &h) * Qubit::zero(),
+                &s.run(h.run(Qubit::zero()))
+            );
+        }
+
+        #[test]
+        fn apply_to_register() {
+            use nalgebra::{U4, U8};
+            let zero = QuantumRegister::<U4>::basis(0b00);
+            assert_eq!(
+                not().apply_to(zero.clone(), 0),
+                QuantumRegister::basis(0b10)
+            );
+            assert_eq!(
+                not().apply_to(zero, 1),
+                QuantumRegister::basis(0b01)
+            );
+            let reg = QuantumRegister::<U8>::w_state();
+            assert_eq!(
+                h().apply_to(reg.clone(), 2),
+                reg.apply_unary(&h(), 2)
+            );
+        }
+
+        #[test]
+        #[should_panic(
+            expected = "Target qubit 2 out of range for a 2 qubit register"
+        )]
+        fn apply_to_out_of_range() {
+            not().apply_to(
+                QuantumRegister::<nalgebra::U4>::basis(0),
+                2,
+            );
+        }
+
+        #[test]
+        fn s_squared_is_z() {
+            let s = s().mat;
+            assert_relative_eq!(s * s, z().mat);
+        }
+
+        #[test]
+        fn rotations() {
+            use crate::consts::PI;
+            let flipped = rx(PI).run(Qubit::zero());
+            assert_relative_eq!(
+                flipped.inner_product(&Qubit::one()).norm(),
+                1.0,
+                epsilon = 1e-6
+            );
+            assert_eq_up_to_phase(&rx(PI), &not(), 1e-6);
+            assert_eq_up_to_phase(&ry(PI), &pauli::y(), 1e-6);
+            assert_eq_up_to_phase(&rz(PI), &z(), 1e-6);
+            let identity = UnaryGate::identity();
+            assert_eq_up_to_phase(&rz(2.0 * PI), &identity, 1e-6);
+            assert_relative_eq!(
+                rz(2.0 * PI).mat,
+                -Matrix::identity(),
+                epsilon = 1e-6
+            );
+            assert_relative_eq!(
+                ry(PI / 2.0).run(Qubit::zero()),
+                &Qubit::plus(),
+                epsilon = 1e-6
+            );
+            for &theta in &[0.1, 1.3, -2.2, 17.0] {
+                assert_relative_eq!(
+                    rz(theta).mat,
+                    UnaryGate::from_parameters((
+                        theta, 0.0, 0.0, 0.0
+                    ))
+                    .mat
+                );
+                for gate in &[rx(theta), ry(theta)] {
+                    assert!(is_unitary(&gate.mat));
+                }
+                // Rotations about one axis add up
+                assert_relative_eq!(
+                    rx(theta).compose(&rx(0.4)).mat,
+                    rx(theta + 0.4).mat,
+                    epsilon = 1e-5
+                );
+                assert_relative_eq!(
+                    ry(theta).compose(&ry(0.4)).mat,
+                    ry(theta + 0.4).mat,
+                    epsilon = 1e-5
+                );
+            }
+        }
+
+        #[test]
+        fn phase_shift() {
+            use crate::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+            assert_relative_eq!(
+                p(FRAC_PI_2).mat,
+                s().mat,
+                epsilon = 1e-6
+            );
+            assert_relative_eq!(p(FRAC_PI_4).mat, t().mat);
+            assert_relative_eq!(p(PI).mat, z().mat, epsilon = 1e-6);
+            for &(lambda, mu) in
+                &[(0.2, 0.9), (-1.5, 3.0), (2.5, 2.5)]
+            {
+                assert_relative_eq!(
+                    p(lambda).compose(&p(mu)).mat,
+                    p(lambda + mu).mat,
+                    epsilon = 1e-6
+                );
+            }
+        }
+
+        #[test]
+        fn u3_gates() {
+            use crate::consts::{FRAC_PI_2, PI};
+            assert_relative_eq!(
+                u3(PI, 0.0, PI).mat,
+                not().mat,
+                epsilon = 1e-6
+            );
+            assert_relative_eq!(
+                u3(FRAC_PI_2, 0.0, PI).mat,
+                h().mat,
+                epsilon = 1e-6
+            );
+            for &(theta, phi, lambda) in &[
+                (0.3, 1.1, -0.4),
+                (2.9, -2.0, 0.7),
+                (12.0, 5.0, 3.0),
+            ] {
+                assert_eq_up_to_phase(
+                    &u3(theta, phi, lambda),
+                    &rz(phi).compose(&ry(theta)).compose(&rz(lambda)),
+                    1e-5,
+                );
+            }
+        }
+
+        #[test]
+        fn identity_does_nothing() {
+            let identity = UnaryGate::identity();
+            for q in &[
+                Qubit::zero(),
+                Qubit::minus(),
+                Qubit::from_theta_phi(1.2, 0.3),
+            ] {
+                assert_eq!(identity.run(q.clone()), *q);
+            }
+            for g in &[h(), t(), rx(0.7)] {
+                assert_eq!(&identity.compose(g), g);
+                assert_eq!(&g.compose(&identity), g);
+            }
+        }
+
+        #[test]
+        fn tensor() {
+            let identity = UnaryGate::identity();
+            let h_i = h().tensor(&identity);
+            let zero_zero = QuantumRegister::from_2_qubits(
+                Qubit::zero(),
+                Qubit::zero(),
+            );
+            assert_relative_eq!(
+                h_i.apply(zero_zero).into_vector(),
+                QuantumRegister::from_2_qubits(
+                    Qubit::plus(),
+                    Qubit::zero()
+                )
+                .into_vector()
+            );
+            let (a, b) =
+                (Qubit::from_theta_phi(0.4, 2.0), Qubit::minus());
+            let gate = t().tensor(&pauli::y());
+            assert_relative_eq!(
+                gate.apply(QuantumRegister::from_2_qubits(
+                    a.clone(),
+                    b.clone()
+                ))
+                .into_vector(),
+                QuantumRegister::from_2_qubits(
+                    t().run(a),
+                    pauli::y().run(b)
+                )
+                .into_vector(),
+                epsilon = 1e-6
+            );
+        }
+
+        #[test]
+        fn dagger() {
+            for gate in &[not(), pauli::y(), z(), h()] {
+                assert_eq!(&gate.dagger(), gate);
+            }
+            let s = s();
+            assert_ne!(s.dagger(), s);
+            assert_relative_eq!(s.dagger().mat, s.compose(&z()).mat);
+            let identity = Matrix::identity();
+            for gate in &[
+                s,
+                t(),
+                UnaryGate::from_parameters((0.1, 2.0, 0.4, 1.3)),
+            ] {
+                assert_relative_eq!(
+                    gate.compose(&gate.dagger()).mat,
+                    identity,
+                    epsilon = 1e-6
+                );
+            }
+        }
+
+        #[test]
+        fn compose() {
+            let identity = UnaryGate::identity();
+            assert_relative_eq!(
+                h().compose(&h()).mat,
+                identity.mat,
+                epsilon = 1e-6
+            );
+            assert_relative_eq!(s().compose(&s()).mat, z().mat);
+            // Runs the right hand gate first
+            let q = Qubit::from_theta_phi(0.7, 0.2);
+            assert_relative_eq!(
+                h().compose(&t()).run(q.clone()),
+                &h().run(t().run(q)),
+                epsilon = 1e-6
+            );
+        }
+
+        #[test]
+        fn t_to_the_fourth_is_z() {
+            let t = t().mat;
+            assert_relative_eq!(t * t, s().mat, epsilon = 1e-6);
+            assert_relative_eq!(
+                t * t * t * t,
+                z().mat,
+                epsilon = 1e-6
+            );
+        }
+
+        #[test]
+        fn parameters_round_trip() {
+            use rand::rngs::SmallRng;
+            use rand::{Rng, SeedableRng};
+            let h = h();
+            let round_trip =
+                UnaryGate::from_parameters(h.to_parameters());
+            assert_relative_eq!(
+                round_trip.mat,
+                h.mat,
+                epsilon = 1e-6
+            );
+
+            let mut rng = SmallRng::seed_from_u64(0x5eed);
+            for _ in 0..100 {
+                let tau = 2.0 * crate::consts::PI;
+                let gate = UnaryGate::from_parameters((
+                    rng.gen_range(0.0, tau),
+                    rng.gen_range(0.0, tau),
+                    rng.gen_range(0.0, tau),
+                    rng.gen_range(0.0, tau),
+                ));
+                let round_trip =
+                    UnaryGate::from_parameters(gate.to_parameters());
+                assert_relative_eq!(
+                    round_trip.mat,
+                    gate.mat,
+                    epsilon = 1e-5
+                );
+            }
+        }
+
+        #[test]
+        fn fidelity_trajectory_stays_high() {
+            let trajectory = h().fidelity_trajectory(
+                &Qubit::from_theta_phi(0.3, 0.9),
+                1000,
+            );
+            assert_eq!(trajectory.len(), 1000);
+            for fidelity in trajectory {
+                assert!(
+                    fidelity > 0.999,
+                    "Fidelity fell to {}",
+                    fidelity
+                );
+            }
+            let rotation =
+                UnaryGate::from_parameters((0.1, 0.7, 2.3, 0.4));
+            for fidelity in
+                rotation.fidelity_trajectory(&Qubit::zero(), 1000)
+            {
+                assert!(
+                    fidelity > 0.999,
+                    "Fidelity fell to {}",
+                    fidelity
+                );
+            }
+        }
+
+        #[test]
+        #[allow(clippy::op_ref)]
         fn h_squared_is_i() {
             let h = h().mat;
-            let i = &h * &h;
-            assert_relative_eq!(i, Matrix::identity());
+            let i = UnaryGate::new(&h * &h);
+            assert_eq_up_to_phase(&i, &UnaryGate::identity(), 1e-6);
         }
         #[test]
         fn pauli_squared_is_i() {
             for i in &[pauli::x(), pauli::y(), pauli::z()] {
                 let m = &i.mat;
-                let i = m * m;
-                assert_relative_eq!(i, Matrix::identity());
+                let i = UnaryGate::new(m * m);
+                assert_eq_up_to_phase(
+                    &i,
+                    &UnaryGate::identity(),
+                    1e-6,
+                );
             }
         }
+        #[test]
+        fn accumulated_phase_of_loop() {
+            use crate::consts::{FRAC_PI_2, PI};
+            let quarter_turn = UnaryGate::from_parameters((
+                FRAC_PI_2, 0.0, 0.0, 0.0,
+            ));
+            let full_turn = vec![quarter_turn; 4];
+            // Going once around the Bloch sphere flips the sign
+            let phase = accumulated_phase(&full_turn, &Qubit::zero());
+            assert_relative_eq!(
+                Complex::exp_ix(phase),
+                -Complex::one(),
+                epsilon = 1e-5
+            );
+            let phase =
+                accumulated_phase(&full_turn[..2], &Qubit::one());
+            assert_relative_eq!(phase, FRAC_PI_2, epsilon = 1e-5);
+            assert_relative_eq!(
+                accumulated_phase(&[h(), not(), h()], &Qubit::one()),
+                PI,
+                epsilon = 1e-5
+            );
+            assert_eq!(accumulated_phase(&[], &Qubit::plus()), 0.0);
+        }
+
+        #[test]
+        #[should_panic(
+            expected = "don't return the qubit to its start"
+        )]
+        fn accumulated_phase_needs_loop() {
+            accumulated_phase(&[not()], &Qubit::zero());
+        }
+
+        #[test]
+        fn eq_up_to_phase() {
+            let y = pauli::y().mat;
+            let ixz = UnaryGate::new(
+                pauli::x().mat * pauli::z().mat * Complex::i(),
+            );
+            let minus_y = UnaryGate::new(-y);
+            assert_ne!(ixz, minus_y);
+            assert_eq_up_to_phase(&ixz, &minus_y, 1e-6);
+            assert!(!h().eq_up_to_phase(&not(), 1e-6));
+        }
+        #[test]
+        #[should_panic(
+            expected = "differ by more than a global phase"
+        )]
+        fn assert_eq_up_to_phase_panics() {
+            assert_eq_up_to_phase(&h(), &z(), 1e-6);
+        }
     }
 }
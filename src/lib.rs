@@ -1,5 +1,29 @@
+pub mod circuit;
+#[cfg(feature = "cli")]
+pub mod cli;
 pub mod complex;
 pub mod gates;
-// pub mod matrix;
+mod linalg;
+pub mod matrix;
+pub mod noise;
+pub mod protocols;
 pub mod qubit;
 pub mod registers;
+pub mod sample;
+
+/// The floating point type everything is computed in: `f32`, or `f64`
+/// with the `double-precision` feature
+#[cfg(not(feature = "double-precision"))]
+pub type Float = f32;
+/// The floating point type everything is computed in: `f32`, or `f64`
+/// with the `double-precision` feature
+#[cfg(feature = "double-precision")]
+pub type Float = f64;
+
+/// Constants such as π, as [`Float`]s
+pub mod consts {
+    #[cfg(not(feature = "double-precision"))]
+    pub use std::f32::consts::*;
+    #[cfg(feature = "double-precision")]
+    pub use std::f64::consts::*;
+}
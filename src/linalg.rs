@@ -0,0 +1,144 @@
+//! Linear algebra over `Complex` that nalgebra only offers for its own
+//! scalar types
+use crate::complex::Complex;
+use crate::Float;
+
+use nalgebra::allocator::Allocator;
+use nalgebra::default_allocator::DefaultAllocator;
+use nalgebra::dimension::DimName;
+use nalgebra::MatrixN;
+
+/// The determinant of a square matrix, by Gaussian elimination with
+/// partial pivoting.
+pub(crate) fn determinant<D: DimName>(
+    mat: &MatrixN<Complex, D>,
+) -> Complex
+where
+    DefaultAllocator: Allocator<Complex, D, D>,
+{
+    let mut mat = mat.clone();
+    let dim = D::dim();
+    let mut det = Complex::one();
+    for col in 0..dim {
+        // Pick the largest remaining entry in this column as the pivot
+        let pivot = (col..dim)
+            .max_by(|&a, &b| {
+                mat[(a, col)]
+                    .mag_square()
+                    .partial_cmp(&mat[(b, col)].mag_square())
+                    .unwrap()
+            })
+            .unwrap();
+        if mat[(pivot, col)] == Complex::zero() {
+            return Complex::zero();
+        }
+        if pivot != col {
+            mat.swap_rows(pivot, col);
+            det = -det;
+        }
+        let p = mat[(col, col)];
+        det *= p;
+        for row in col + 1..dim {
+            let factor = mat[(row, col)] / p;
+            for k in col..dim {
+                let sub = factor * mat[(col, k)];
+                mat[(row, k)] -= sub;
+            }
+        }
+    }
+    det
+}
+
+/// Make the columns of `mat` orthonormal in place, by Gram-Schmidt.
+/// A matrix that's already unitary up to rounding only moves by about
+/// that rounding.
+pub(crate) fn orthonormalize<D: DimName>(
+    mat: &mut MatrixN<Complex, D>,
+) where
+    DefaultAllocator: Allocator<Complex, D, D>,
+{
+    let dim = D::dim();
+    for col in 0..dim {
+        // Orthogonalizing twice keeps the rounding error well under the
+        // unitarity tolerance
+        for prev in (0..col).chain(0..col) {
+            let overlap = (0..dim).fold(Complex::zero(), |acc, k| {
+                acc + mat[(k, prev)].conj() * mat[(k, col)]
+            });
+            for k in 0..dim {
+                let projection = overlap * mat[(k, prev)];
+                mat[(k, col)] -= projection;
+            }
+        }
+        let norm = (0..dim)
+            .map(|k| mat[(k, col)].mag_square())
+            .sum::<Float>()
+            .sqrt();
+        for k in 0..dim {
+            mat[(k, col)] = mat[(k, col)] * (1.0 / norm);
+        }
+    }
+}
+
+/// Whether `mat` times its conjugate transpose is the identity, to
+/// within `1e-6`.
+pub(crate) fn is_unitary<D: DimName>(
+    mat: &MatrixN<Complex, D>,
+) -> bool
+where
+    DefaultAllocator: Allocator<Complex, D, D>,
+{
+    let x = mat * mat.transpose().map(|x| x.conj());
+    approx::relative_eq!(x, MatrixN::identity(), epsilon = 1.0e-6)
+}
+
+/// Whether `a` and `b` are equal once a global phase is factored out,
+/// with each entry matching within `epsilon`. The phase is the one
+/// that best lines them up, the argument of `⟨a|b⟩`.
+pub(crate) fn eq_up_to_phase(
+    a: &[Complex],
+    b: &[Complex],
+    epsilon: Float,
+) -> bool {
+    let overlap = a
+        .iter()
+        .zip(b)
+        .fold(Complex::zero(), |acc, (x, y)| acc + x.conj() * *y);
+    if overlap == Complex::zero() {
+        return false;
+    }
+    let phase = Complex::exp_ix(overlap.arg());
+    a.iter()
+        .zip(b)
+        .all(|(x, y)| (*x * phase - *y).norm() <= epsilon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use nalgebra::{Matrix2, Matrix3};
+
+    #[test]
+    fn determinant_small() {
+        let m = Matrix2::new(
+            Complex::new(1.0, 0.0),
+            Complex::new(2.0, 0.0),
+            Complex::new(3.0, 0.0),
+            Complex::new(4.0, 0.0),
+        );
+        assert_relative_eq!(determinant(&m), Complex::new(-2.0, 0.0));
+        let m = Matrix3::new(
+            Complex::zero(),
+            Complex::one(),
+            Complex::zero(),
+            Complex::one(),
+            Complex::zero(),
+            Complex::zero(),
+            Complex::zero(),
+            Complex::zero(),
+            Complex::i(),
+        );
+        assert_eq!(determinant(&m), -Complex::i());
+    }
+}
@@ -0,0 +1,26 @@
+use clap::error::ErrorKind;
+use toy_quant::cli::{self, Args};
+use toy_quant::registers::classical::ClassicalRegister;
+use toy_quant::Float;
+
+fn main() {
+    let matches = cli::command().get_matches();
+    let Args { circuit, shots } =
+        match cli::args_from_matches(&matches) {
+            Ok(args) => args,
+            Err(e) => cli::command()
+                .error(ErrorKind::ValueValidation, e)
+                .exit(),
+        };
+    let counts =
+        cli::sample_counts(&circuit, shots, &mut rand::thread_rng());
+    for (idx, count) in counts.iter().enumerate() {
+        let reg = ClassicalRegister::from(idx as u64);
+        println!(
+            "∣{:width$}⟩ * {}",
+            reg,
+            *count as Float / shots as Float,
+            width = circuit.n_qubits()
+        );
+    }
+}
@@ -0,0 +1,312 @@
+//! A minimal 2x2 matrix, generic over the element type
+use crate::complex::Complex;
+use crate::Float;
+
+use num_traits::identities::{One, Zero};
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// A 2x2 matrix, stored row by row
+///
+/// ```text
+/// | .0 .1 |
+/// | .2 .3 |
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Matrix2x2<T>(pub T, pub T, pub T, pub T);
+
+impl<T: Copy> Matrix2x2<T> {
+    /// Swap rows and columns
+    pub fn transpose(&self) -> Self {
+        Matrix2x2(self.0, self.2, self.1, self.3)
+    }
+
+    /// Apply `f` to each element
+    pub fn map<U, F: Fn(T) -> U>(&self, f: F) -> Matrix2x2<U> {
+        Matrix2x2(f(self.0), f(self.1), f(self.2), f(self.3))
+    }
+}
+
+impl<T: Copy + Add<Output = T>> Matrix2x2<T> {
+    /// The sum of the diagonal
+    pub fn trace(&self) -> T {
+        self.0 + self.3
+    }
+}
+
+impl<T: Zero + One> Matrix2x2<T> {
+    pub fn identity() -> Self {
+        Matrix2x2(T::one(), T::zero(), T::zero(), T::one())
+    }
+}
+
+impl<T> Matrix2x2<T>
+where
+    T: Copy + Mul<Output = T> + Sub<Output = T>,
+{
+    /// The determinant
+    pub fn det(&self) -> T {
+        self.0 * self.3 - self.1 * self.2
+    }
+}
+
+impl<T> Matrix2x2<T>
+where
+    T: Copy
+        + Zero
+        + PartialEq
+        + Mul<Output = T>
+        + Sub<Output = T>
+        + Div<Output = T>
+        + Neg<Output = T>,
+{
+    /// The inverse, or `None` if the matrix is singular
+    pub fn inv(&self) -> Option<Self> {
+        let det = self.det();
+        if det == T::zero() {
+            return None;
+        }
+        Some(Matrix2x2(
+            self.3 / det,
+            -self.1 / det,
+            -self.2 / det,
+            self.0 / det,
+        ))
+    }
+}
+
+impl Matrix2x2<Complex> {
+    /// The conjugate transpose. A matrix is unitary when this is its
+    /// inverse.
+    pub fn dagger(&self) -> Self {
+        self.transpose().map(Complex::conj)
+    }
+
+    /// The [operator norm](https://en.wikipedia.org/wiki/Operator_norm),
+    /// the largest singular value. This is 1 for a unitary matrix.
+    pub fn operator_norm(&self) -> Float {
+        // M†M is hermitian, so its eigenvalues are real, and the larger
+        // one has a closed form.
+        let m = &self.dagger() * self;
+        let (p, q, r) = (m.0.re(), m.1, m.3.re());
+        let mean = (p + r) / 2.0;
+        let spread = ((p - r) / 2.0).hypot(q.norm());
+        (mean + spread).max(0.0).sqrt()
+    }
+}
+
+impl<'a, T> Mul<&'a Matrix2x2<T>> for &'a Matrix2x2<T>
+where
+    T: Copy + Add<Output = T> + Mul<Output = T>,
+{
+    type Output = Matrix2x2<T>;
+    fn mul(self, other: &'a Matrix2x2<T>) -> Matrix2x2<T> {
+        Matrix2x2(
+            self.0 * other.0 + self.1 * other.2,
+            self.0 * other.1 + self.1 * other.3,
+            self.2 * other.0 + self.3 * other.2,
+            self.2 * other.1 + self.3 * other.3,
+        )
+    }
+}
+
+impl<T> Mul<Matrix2x2<T>> for Matrix2x2<T>
+where
+    T: Copy + Add<Output = T> + Mul<Output = T>,
+{
+    type Output = Matrix2x2<T>;
+    fn mul(self, other: Matrix2x2<T>) -> Matrix2x2<T> {
+        &self * &other
+    }
+}
+
+impl From<Matrix2x2<Complex>> for nalgebra::Matrix2<Complex> {
+    fn from(m: Matrix2x2<Complex>) -> Self {
+        nalgebra::Matrix2::new(m.0, m.1, m.2, m.3)
+    }
+}
+
+impl From<nalgebra::Matrix2<Complex>> for Matrix2x2<Complex> {
+    fn from(m: nalgebra::Matrix2<Complex>) -> Self {
+        Matrix2x2(m[(0, 0)], m[(0, 1)], m[(1, 0)], m[(1, 1)])
+    }
+}
+
+/// Scale every element
+impl<T: Copy + Mul<Output = T>> Mul<T> for Matrix2x2<T> {
+    type Output = Matrix2x2<T>;
+    fn mul(self, scale: T) -> Matrix2x2<T> {
+        self.map(|x| x * scale)
+    }
+}
+
+impl<T: Add<Output = T>> Add for Matrix2x2<T> {
+    type Output = Matrix2x2<T>;
+    fn add(self, other: Matrix2x2<T>) -> Matrix2x2<T> {
+        Matrix2x2(
+            self.0 + other.0,
+            self.1 + other.1,
+            self.2 + other.2,
+            self.3 + other.3,
+        )
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Matrix2x2<T> {
+    type Output = Matrix2x2<T>;
+    fn sub(self, other: Matrix2x2<T>) -> Matrix2x2<T> {
+        Matrix2x2(
+            self.0 - other.0,
+            self.1 - other.1,
+            self.2 - other.2,
+            self.3 - other.3,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts::FRAC_1_SQRT_2;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn det_and_inv() {
+        let m = Matrix2x2(1.0, 2.0, 3.0, 4.0);
+        assert_eq!(m.det(), -2.0);
+        assert_eq!(m.transpose(), Matrix2x2(1.0, 3.0, 2.0, 4.0));
+        assert_eq!(m * m.inv().unwrap(), Matrix2x2::identity());
+        assert_eq!(Matrix2x2(1.0, 2.0, 2.0, 4.0).inv(), None);
+    }
+
+    #[test]
+    fn dagger_and_trace() {
+        let y = Matrix2x2(
+            Complex::zero(),
+            -Complex::i(),
+            Complex::i(),
+            Complex::zero(),
+        );
+        assert_eq!(y.dagger(), y);
+        assert_eq!(y * y.dagger(), Matrix2x2::identity());
+        assert_eq!(y.trace(), Complex::zero());
+        let m = Matrix2x2(
+            Complex::new(1.0, 2.0),
+            Complex::new(3.0, 4.0),
+            Complex::zero(),
+            Complex::i(),
+        );
+        assert_eq!(
+            m.dagger(),
+            Matrix2x2(
+                Complex::new(1.0, -2.0),
+                Complex::zero(),
+                Complex::new(3.0, -4.0),
+                -Complex::i(),
+            )
+        );
+        assert_eq!(m.trace(), Complex::new(1.0, 3.0));
+        assert_eq!(Matrix2x2(1, 2, 3, 4).trace(), 5);
+    }
+
+    #[test]
+    fn hash_complex_matrices() {
+        use std::collections::HashSet;
+        let i = Matrix2x2::<Complex>::identity();
+        let flipped =
+            (i * Complex::from_re(-1.0)) * Complex::from_re(-1.0);
+        let set: HashSet<_> =
+            vec![i, flipped, i.dagger()].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn scale_add_sub() {
+        let i = Matrix2x2::<Float>::identity();
+        assert_eq!(i + i, Matrix2x2(2.0, 0.0, 0.0, 2.0));
+        assert_eq!(i - i, Matrix2x2(0.0, 0.0, 0.0, 0.0));
+        let m = Matrix2x2(1.0, -2.0, 3.0, 0.5);
+        assert_eq!(m * 2.0, Matrix2x2(2.0, -4.0, 6.0, 1.0));
+        assert_eq!(m * 2.0 - m, m);
+        // The Hadamard gate is (X + Z)/√2
+        let (one, zero) = (Complex::one(), Complex::zero());
+        let x = Matrix2x2(zero, one, one, zero);
+        let z = Matrix2x2(one, zero, zero, -one);
+        let h = (x + z) * Complex::from_re(FRAC_1_SQRT_2);
+        assert_relative_eq!(h.operator_norm(), 1.0, epsilon = 1e-6);
+        let hh = h * h;
+        assert_relative_eq!(hh.0, one, epsilon = 1e-6);
+        assert_relative_eq!(hh.1, zero, epsilon = 1e-6);
+        assert_relative_eq!(hh.2, zero, epsilon = 1e-6);
+        assert_relative_eq!(hh.3, one, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn operator_norm_of_unitary_is_1() {
+        let h = Matrix2x2(
+            Complex::one(),
+            Complex::one(),
+            Complex::one(),
+            -Complex::one(),
+        )
+        .map(|x| x * FRAC_1_SQRT_2);
+        assert_relative_eq!(h.operator_norm(), 1.0, epsilon = 1e-6);
+        let y = Matrix2x2(
+            Complex::zero(),
+            -Complex::i(),
+            Complex::i(),
+            Complex::zero(),
+        );
+        assert_relative_eq!(y.operator_norm(), 1.0);
+    }
+
+    #[test]
+    fn nalgebra_round_trip() {
+        let m = Matrix2x2(
+            Complex::new(1.0, 2.0),
+            Complex::new(3.0, 4.0),
+            Complex::zero(),
+            Complex::i(),
+        );
+        let na = nalgebra::Matrix2::from(m);
+        assert_eq!(na[(0, 1)], m.1);
+        assert_eq!(na[(1, 0)], m.2);
+        assert_eq!(Matrix2x2::from(na), m);
+    }
+
+    #[test]
+    fn matches_nalgebra_on_unitaries() {
+        use crate::gates::unitary::UnaryGate;
+        use crate::linalg::determinant;
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let mut rng = SmallRng::seed_from_u64(8);
+        for _ in 0..20 {
+            let na = *UnaryGate::random(&mut rng).matrix();
+            let m = Matrix2x2::from(na);
+            assert_relative_eq!(
+                m.det(),
+                determinant(&na),
+                epsilon = 1e-6
+            );
+            assert_eq!(
+                nalgebra::Matrix2::from(m.dagger()),
+                na.transpose().map(|x| x.conj())
+            );
+            assert_eq!(m.trace(), na[(0, 0)] + na[(1, 1)]);
+        }
+    }
+
+    #[test]
+    fn operator_norm_scales() {
+        let two = Matrix2x2::<Complex>::identity().map(|x| x * 2.0);
+        assert_relative_eq!(two.operator_norm(), 2.0);
+        let m = Matrix2x2(
+            Complex::from_re(3.0),
+            Complex::zero(),
+            Complex::zero(),
+            Complex::i(),
+        );
+        assert_relative_eq!(m.operator_norm(), 3.0);
+    }
+}
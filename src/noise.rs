@@ -0,0 +1,234 @@
+//! Noise channels on a single qubit, acting on its density matrix
+use crate::complex::Complex;
+use crate::gates::unitary::{apply_matrix_to_slice, gates::pauli};
+use crate::registers::density::DensityMatrix;
+use crate::Float;
+
+use nalgebra::{Matrix2, U2};
+use rand::Rng;
+
+type Matrix = Matrix2<Complex>;
+
+/// A quantum channel, given by its
+/// [Kraus operators](https://en.wikipedia.org/wiki/Quantum_operation#Kraus_operators)
+/// `K_i`, which sends `ρ` to `Σ K_i ρ K_i†`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Channel {
+    kraus: Vec<Matrix>,
+}
+
+impl Channel {
+    /// The Kraus operators of the channel
+    pub fn kraus(&self) -> &[Matrix] {
+        &self.kraus
+    }
+
+    /// Run the channel on a single qubit state
+    pub fn apply(
+        &self,
+        rho: &DensityMatrix<U2>,
+    ) -> DensityMatrix<U2> {
+        let mat = self.kraus.iter().fold(
+            Matrix::from_element(Complex::zero()),
+            |acc, k| {
+                acc + k * rho.mat * k.transpose().map(|x| x.conj())
+            },
+        );
+        DensityMatrix { mat }
+    }
+
+    /// A single noise event on qubit `target` of a pure state, as in
+    /// [`Qubit::apply_kraus`](crate::qubit::Qubit::apply_kraus)
+    pub(crate) fn apply_to_slice<R: Rng>(
+        &self,
+        amps: &mut [Complex],
+        target: usize,
+        rng: &mut R,
+    ) {
+        apply_kraus_to_slice(&self.kraus, amps, target, rng)
+    }
+}
+
+/// Pick one of the Kraus operators `ops` with probability `‖K|ψ⟩‖²`,
+/// run it on qubit `target` of `amps`, and renormalize.
+///
+/// Panics if every operator sends the state to zero.
+pub(crate) fn apply_kraus_to_slice<R: Rng>(
+    ops: &[Matrix],
+    amps: &mut [Complex],
+    target: usize,
+    rng: &mut R,
+) {
+    let outcomes: Vec<_> = ops
+        .iter()
+        .map(|k| {
+            let mut out = amps.to_vec();
+            apply_matrix_to_slice(k, &mut out, target);
+            let prob =
+                out.iter().map(|x| x.mag_square()).sum::<Float>();
+            (out, prob)
+        })
+        .filter(|(_, prob)| *prob > 0.0)
+        .collect();
+    assert!(
+        !outcomes.is_empty(),
+        "The Kraus operators annihilate the state"
+    );
+    let total: Float = outcomes.iter().map(|(_, prob)| prob).sum();
+    let mut sample = rng.gen::<Float>() * total;
+    let mut outcomes = outcomes.into_iter();
+    let (chosen, prob) = loop {
+        let (out, prob) = outcomes.next().unwrap();
+        // Rounding can leave `sample` past the end, so take the last
+        if sample < prob || outcomes.len() == 0 {
+            break (out, prob);
+        }
+        sample -= prob;
+    };
+    let scale = Complex::from_re(prob.sqrt().recip());
+    for (amp, out) in amps.iter_mut().zip(chosen) {
+        *amp = out * scale;
+    }
+}
+
+/// Amplitude damping, the decay of `|1⟩` to `|0⟩` with probability
+/// `gamma`, as from energy loss.
+///
+/// Panics unless `0 <= gamma <= 1`.
+pub fn amplitude_damping(gamma: Float) -> Channel {
+    assert!(
+        (0.0..=1.0).contains(&gamma),
+        "Damping {} isn't a probability",
+        gamma
+    );
+    let (zero, one) = (Complex::zero(), Complex::one());
+    let k0 = Matrix::new(
+        one,
+        zero,
+        zero,
+        Complex::from_re((1.0 - gamma).sqrt()),
+    );
+    let k1 =
+        Matrix::new(zero, Complex::from_re(gamma.sqrt()), zero, zero);
+    Channel {
+        kraus: vec![k0, k1],
+    }
+}
+
+/// The depolarizing channel `ρ -> (1 - p) ρ + p I/2`, which replaces the
+/// state with the maximally mixed one with probability `p`.
+///
+/// Panics unless `0 <= p <= 1`.
+pub fn depolarizing(p: Float) -> Channel {
+    assert!(
+        (0.0..=1.0).contains(&p),
+        "Depolarizing {} isn't a probability",
+        p
+    );
+    let keep = Complex::from_re((1.0 - 0.75 * p).sqrt());
+    let flip = Complex::from_re((p / 4.0).sqrt());
+    let mut kraus = vec![Matrix::identity() * keep];
+    kraus.extend(
+        [pauli::x(), pauli::y(), pauli::z()]
+            .iter()
+            .map(|gate| gate.mat * flip),
+    );
+    Channel { kraus }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qubit::Qubit;
+    use crate::registers::quantum::QuantumRegister;
+    use approx::assert_relative_eq;
+
+    fn states() -> Vec<DensityMatrix<U2>> {
+        [
+            Qubit::zero(),
+            Qubit::one(),
+            Qubit::plus(),
+            Qubit::from_theta_phi(1.1, -0.6),
+        ]
+        .iter()
+        .map(|q| {
+            DensityMatrix::from_pure(&QuantumRegister::from(
+                q.clone(),
+            ))
+        })
+        .collect()
+    }
+
+    #[test]
+    fn kraus_operators_are_complete() {
+        for channel in &[
+            amplitude_damping(0.3),
+            amplitude_damping(1.0),
+            depolarizing(0.0),
+            depolarizing(0.6),
+        ] {
+            let sum = channel.kraus.iter().fold(
+                Matrix::from_element(Complex::zero()),
+                |acc, k| acc + k.transpose().map(|x| x.conj()) * k,
+            );
+            assert_relative_eq!(
+                sum,
+                Matrix::identity(),
+                epsilon = 1e-6
+            );
+        }
+    }
+
+    #[test]
+    fn full_depolarizing_mixes_completely() {
+        let mixed = Matrix::identity() * Complex::from_re(0.5);
+        for rho in states() {
+            let out = depolarizing(1.0).apply(&rho);
+            assert_relative_eq!(out.mat, mixed, epsilon = 1e-6);
+            assert_relative_eq!(out.purity(), 0.5, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn no_noise_is_identity() {
+        for rho in states() {
+            assert_relative_eq!(
+                amplitude_damping(0.0).apply(&rho).mat,
+                rho.mat,
+                epsilon = 1e-6
+            );
+            assert_relative_eq!(
+                depolarizing(0.0).apply(&rho).mat,
+                rho.mat,
+                epsilon = 1e-6
+            );
+        }
+    }
+
+    #[test]
+    fn full_damping_decays_to_zero() {
+        let zero = &states()[0];
+        for rho in states() {
+            let out = amplitude_damping(1.0).apply(&rho);
+            assert_relative_eq!(out.mat, zero.mat, epsilon = 1e-6);
+        }
+        // Partial damping moves part of the |1⟩ population
+        let out = amplitude_damping(0.25).apply(&states()[1]);
+        assert_relative_eq!(
+            out.mat[(0, 0)].re(),
+            0.25,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            out.mat[(1, 1)].re(),
+            0.75,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't a probability")]
+    fn damping_must_be_a_probability() {
+        amplitude_damping(1.5);
+    }
+}
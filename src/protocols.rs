@@ -0,0 +1,44 @@
+//! Protocols built out of gates and measurement
+use crate::gates::binary::gates::cnot;
+use crate::gates::unitary::gates::{h, pauli};
+use crate::qubit::Qubit;
+use crate::registers::quantum::QuantumRegister;
+
+use nalgebra::{U2, U4, U8};
+use rand::Rng;
+
+/// [Teleport](https://en.wikipedia.org/wiki/Quantum_teleportation)
+/// `input` from qubit 0 onto qubit 2, using a Bell pair shared between
+/// qubits 1 and 2, and two classical bits.
+///
+/// Returns the two measured bits, `(z, x)`, and the qubit that arrives.
+pub fn teleport(input: &Qubit) -> (bool, bool, Qubit) {
+    teleport_with_rng(input, &mut rand::thread_rng())
+}
+
+/// [`teleport`], drawing the measurements from `rng`
+pub fn teleport_with_rng<R: Rng>(
+    input: &Qubit,
+    rng: &mut R,
+) -> (bool, bool, Qubit) {
+    let reg: QuantumRegister<U8> =
+        QuantumRegister::<U2>::from(input.clone())
+            .tensor(&QuantumRegister::<U4>::basis(0b00));
+    // Share a Bell pair between qubits 1 and 2
+    let reg = cnot().apply_to(reg.apply_unary(&h(), 1), 1, 2);
+    // Measure qubits 0 and 1 in the Bell basis
+    let mut reg = cnot().apply_to(reg, 0, 1).apply_unary(&h(), 0);
+    let z_bit = reg.measure_qubit_with_rng(0, rng);
+    let x_bit = reg.measure_qubit_with_rng(1, rng);
+    // The two bits say how to fix up qubit 2
+    if x_bit {
+        reg = reg.apply_unary(&pauli::x(), 2);
+    }
+    if z_bit {
+        reg = reg.apply_unary(&pauli::z(), 2);
+    }
+    let output = reg
+        .extract_qubit(2)
+        .expect("Qubit 2 is unentangled after measurement");
+    (z_bit, x_bit, output)
+}
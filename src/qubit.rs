@@ -1,14 +1,16 @@
 //! A single unentangled qubit
 
-use std::f32::consts::FRAC_1_SQRT_2;
+use crate::consts::FRAC_1_SQRT_2;
 use std::ops::Neg;
 
-use approx::assert_relative_eq;
-use nalgebra::Vector2;
+use approx::{assert_relative_eq, relative_eq};
+use nalgebra::{Matrix2, Vector2};
 use rand::prelude::*;
-use rand::rngs::SmallRng;
 
 use crate::complex::Complex;
+use crate::gates::unitary::UnaryGate;
+use crate::noise::apply_kraus_to_slice;
+use crate::Float;
 
 use approx::{AbsDiffEq, RelativeEq};
 
@@ -18,26 +20,100 @@ pub struct Qubit {
 }
 
 impl Qubit {
+    /// The probability of measuring `|0⟩`
+    // A no-op with double-precision, where `Float` is f64
+    #[allow(clippy::useless_conversion)]
+    pub fn bias_zero(&self) -> f64 {
+        self.inner.index(0).mag_square().into()
+    }
+
+    /// Measure the qubit, returning true for `|0⟩`. This samples against
+    /// [`bias_zero`](Self::bias_zero) using the thread local RNG, so it's
+    /// cheap to call in a loop.
     pub fn sample_is_zero(&self) -> bool {
-        SmallRng::from_entropy()
-            .gen_bool(self.inner.index(0).mag_square().into())
+        self.sample_is_zero_with_rng(&mut rand::thread_rng())
     }
     pub fn sample_is_one(&self) -> bool {
         !self.sample_is_zero()
     }
-    pub fn sample(&self) -> f32 {
+    pub fn sample(&self) -> Float {
         if self.sample_is_zero() {
             0.0
         } else {
             1.0
         }
     }
+
+    /// [`sample_is_zero`](Self::sample_is_zero), drawing randomness from
+    /// `rng`
+    pub fn sample_is_zero_with_rng<R: Rng>(
+        &self,
+        rng: &mut R,
+    ) -> bool {
+        rng.gen_bool(self.bias_zero())
+    }
+
+    /// [`sample_is_one`](Self::sample_is_one), drawing randomness from
+    /// `rng`
+    pub fn sample_is_one_with_rng<R: Rng>(
+        &self,
+        rng: &mut R,
+    ) -> bool {
+        !self.sample_is_zero_with_rng(rng)
+    }
+
+    /// [`sample`](Self::sample), drawing randomness from `rng`
+    pub fn sample_with_rng<R: Rng>(&self, rng: &mut R) -> Float {
+        if self.sample_is_zero_with_rng(rng) {
+            0.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Measure the qubit against a given random `target` in `[0, 1)`,
+    /// returning true for `|1⟩`, which comes up once `target` reaches
+    /// `|p_0|²`. This makes edge cases easy to test.
+    pub fn sample_with_target(&self, target: Float) -> bool {
+        target >= self.inner[0].mag_square()
+    }
+
+    /// Scale the amplitudes back to unit length. The zero vector is left
+    /// as it is.
+    pub fn normalize(&mut self) {
+        let norm = self.norm();
+        if norm != 0.0 {
+            self.inner /= Complex::from_re(norm);
+        }
+    }
+
+    /// The length of the amplitude vector, which is 1 for a valid qubit
+    pub fn norm(&self) -> Float {
+        self.norm_squared().sqrt()
+    }
+
+    /// `|p_0|² + |p_1|²`, the total probability
+    pub fn norm_squared(&self) -> Float {
+        self.inner.iter().map(|x| x.mag_square()).sum()
+    }
+
     pub fn new(p_0: Complex, p_1: Complex) -> Self {
         assert_relative_eq!(1.0, p_0.mag_square() + p_1.mag_square());
         Qubit {
             inner: Vector2::new(p_0, p_1),
         }
     }
+
+    /// Like [`new`](Self::new), but scales the amplitudes to unit length
+    /// first rather than requiring it.
+    ///
+    /// Panics if both amplitudes are zero.
+    pub fn from_amplitudes(p_0: Complex, p_1: Complex) -> Self {
+        let norm = p_0.norm().hypot(p_1.norm());
+        assert!(norm > 0.0, "A qubit can't have all zero amplitudes");
+        Qubit::new(p_0 / norm, p_1 / norm)
+    }
+
     pub fn zero() -> Self {
         Self::new(Complex::one(), Complex::zero())
     }
@@ -53,23 +129,257 @@ impl Qubit {
         Self::new(FRAC_1_SQRT_2.into(), (-FRAC_1_SQRT_2).into())
     }
 
-    pub fn from_theta_phi(theta: f32, phi: f32) -> Self {
+    pub fn from_theta_phi(theta: Float, phi: Float) -> Self {
         Qubit::new(
             (theta / 2.0).cos().into(),
             Complex::exp_ix(phi) * (theta / 2.0).sin(),
         )
     }
 
+    /// A random qubit, uniformly distributed over the Bloch sphere
+    pub fn random<R: Rng>(rng: &mut R) -> Self {
+        // Picking cos θ uniformly, rather than θ, keeps the points from
+        // bunching up at the poles
+        let theta = (1.0 - 2.0 * rng.gen::<Float>()).acos();
+        let phi = rng.gen_range(0.0, 2.0 * crate::consts::PI);
+        // Rounding in cos and sin can leave this just far enough off
+        // unit length to fail the check in `from_theta_phi`, so skip it
+        // and rescale instead
+        let mut q = Qubit {
+            inner: Vector2::new(
+                (theta / 2.0).cos().into(),
+                Complex::exp_ix(phi) * (theta / 2.0).sin(),
+            ),
+        };
+        q.normalize();
+        q
+    }
+
+    /// The qubit at the Bloch sphere point in the direction of
+    /// `(x, y, z)`, which needn't be unit length.
+    ///
+    /// Panics on the zero vector, which has no direction.
+    pub fn from_bloch(x: Float, y: Float, z: Float) -> Self {
+        let length = (x * x + y * y + z * z).sqrt();
+        assert!(
+            length > 0.0,
+            "The zero vector isn't on the Bloch sphere"
+        );
+        // Clamp away rounding that would put acos out of its domain
+        let theta = (z / length).clamp(-1.0, 1.0).acos();
+        Qubit::from_theta_phi(theta, y.atan2(x))
+    }
+
     pub fn from_theta_phi_gamma(
-        theta: f32,
-        phi: f32,
-        gamma: f32,
+        theta: Float,
+        phi: Float,
+        gamma: Float,
     ) -> Self {
         let phase_shift = Complex::exp_ix(gamma);
         let ket_0: Complex = (theta / 2.0).cos().into();
         let ket_1 = Complex::exp_ix(phi) * (theta / 2.0).sin();
         Qubit::new(phase_shift * ket_0, phase_shift * ket_1)
     }
+
+    /// The point `(x, y, z)` on the
+    /// [Bloch sphere](https://en.wikipedia.org/wiki/Bloch_sphere)
+    /// representing the qubit
+    pub fn bloch(&self) -> (Float, Float, Float) {
+        let (alpha, beta) = (self.inner[0], self.inner[1]);
+        let cross = alpha.conj() * beta;
+        (
+            2.0 * cross.re(),
+            2.0 * cross.im(),
+            alpha.mag_square() - beta.mag_square(),
+        )
+    }
+
+    /// The expectation value ⟨ψ|O|ψ⟩ of a Hermitian observable
+    pub fn expectation(&self, observable: &UnaryGate) -> Float {
+        self.inner_product(&observable.run(self.clone())).re()
+    }
+
+    /// Estimate [`expectation`](Self::expectation) the way a real device
+    /// would, by measuring `shots` times in the observable's eigenbasis
+    /// and averaging the outcomes.
+    ///
+    /// Panics if there are no shots, or the observable isn't Hermitian.
+    /// Being a gate it's also unitary, so its eigenvalues are ±1, like
+    /// the Pauli gates.
+    pub fn sampled_expectation<R: Rng>(
+        &self,
+        observable: &UnaryGate,
+        shots: usize,
+        rng: &mut R,
+    ) -> Float {
+        assert!(
+            shots > 0,
+            "Can't estimate an expectation from 0 shots"
+        );
+        let mat = observable.mat;
+        assert!(
+            relative_eq!(
+                mat,
+                mat.transpose().map(|x| x.conj()),
+                epsilon = 1.0e-6
+            ),
+            "An observable must be Hermitian"
+        );
+        // (I ± O)/2 projects onto the ±1 eigenspace, so its longer
+        // column is an eigenvector. An eigenvector column has length at
+        // least 1/√2, and one of the two eigenspaces isn't empty.
+        let first = [1.0, -1.0]
+            .iter()
+            .find_map(|&sign: &Float| {
+                let p = (Matrix2::identity()
+                    + mat * Complex::from_re(sign))
+                    * Complex::from_re(0.5);
+                let column = |c| Qubit {
+                    inner: Vector2::new(p[(0, c)], p[(1, c)]),
+                };
+                let (a, b) = (column(0), column(1));
+                let mut v = if a.norm_squared() >= b.norm_squared() {
+                    a
+                } else {
+                    b
+                };
+                if v.norm() > 0.5 {
+                    v.normalize();
+                    Some(v)
+                } else {
+                    None
+                }
+            })
+            .expect("A Hermitian observable has an eigenvector");
+        // In two dimensions, the other eigenvector is orthogonal to it
+        let second = Qubit {
+            inner: Vector2::new(
+                -first.inner[1].conj(),
+                first.inner[0].conj(),
+            ),
+        };
+        let eigenvalue =
+            |v: &Qubit| v.expectation(observable).signum();
+        let values = [eigenvalue(&first), eigenvalue(&second)];
+        // Rotate so the eigenvectors become |0⟩ and |1⟩, and measure
+        let rotated = Qubit {
+            inner: Vector2::new(
+                first.inner_product(self),
+                second.inner_product(self),
+            ),
+        };
+        let total: Float = (0..shots)
+            .map(|_| {
+                values[rotated.sample_with_target(rng.gen()) as usize]
+            })
+            .sum();
+        total / shots as Float
+    }
+
+    /// The inner product ⟨self|other⟩
+    pub fn inner_product(&self, other: &Qubit) -> Complex {
+        self.inner[0].conj() * other.inner[0]
+            + self.inner[1].conj() * other.inner[1]
+    }
+
+    /// The [fidelity](https://en.wikipedia.org/wiki/Fidelity_of_quantum_states)
+    /// |⟨self|other⟩|², 1 for the same state and 0 for orthogonal ones
+    pub fn fidelity(&self, other: &Qubit) -> Float {
+        self.inner_product(other).mag_square()
+    }
+
+    /// The same state with the global phase removed, so that the first
+    /// nonzero amplitude is real and positive.
+    pub fn canonical(&self) -> Qubit {
+        let lead = if self.inner[0] == Complex::zero() {
+            self.inner[1]
+        } else {
+            self.inner[0]
+        };
+        let phase = Complex::exp_ix(-lead.arg());
+        Qubit {
+            inner: self.inner.map(|x| x * phase),
+        }
+    }
+
+    /// A single noise event from the channel with Kraus operators `ops`:
+    /// one operator `K` is picked with probability `‖K|ψ⟩‖²`, applied,
+    /// and the state renormalized.
+    ///
+    /// Panics if every operator sends the state to zero.
+    pub fn apply_kraus<R: Rng>(
+        &mut self,
+        ops: &[Matrix2<Complex>],
+        rng: &mut R,
+    ) {
+        apply_kraus_to_slice(ops, self.inner.as_mut_slice(), 0, rng);
+    }
+}
+
+/// The matrix of pairwise overlaps ⟨ψᵢ|ψⱼ⟩ between `qubits`
+pub fn gram_matrix(qubits: &[Qubit]) -> Vec<Vec<Complex>> {
+    qubits
+        .iter()
+        .map(|a| qubits.iter().map(|b| a.inner_product(b)).collect())
+        .collect()
+}
+
+/// Serialized as its two amplitudes
+#[cfg(feature = "serde")]
+impl serde::Serialize for Qubit {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(
+            &(self.inner[0], self.inner[1]),
+            serializer,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Qubit {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let (p_0, p_1): (Complex, Complex) =
+            serde::Deserialize::deserialize(deserializer)?;
+        if !approx::relative_eq!(
+            1.0,
+            p_0.mag_square() + p_1.mag_square()
+        ) {
+            return Err(serde::de::Error::custom(
+                "qubit amplitudes aren't normalized",
+            ));
+        }
+        Ok(Qubit::new(p_0, p_1))
+    }
+}
+
+/// The basis state `|1⟩` for true, and `|0⟩` for false
+impl From<bool> for Qubit {
+    fn from(bit: bool) -> Self {
+        if bit {
+            Qubit::one()
+        } else {
+            Qubit::zero()
+        }
+    }
+}
+
+/// The basis state `|0⟩` or `|1⟩`.
+///
+/// Panics for anything but 0 or 1.
+impl From<u8> for Qubit {
+    fn from(bit: u8) -> Self {
+        assert!(
+            bit <= 1,
+            "A qubit basis state is 0 or 1, not {}",
+            bit
+        );
+        Qubit::from(bit == 1)
+    }
 }
 
 impl Neg for Qubit {
@@ -121,6 +431,226 @@ impl RelativeEq for Qubit {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::noise::amplitude_damping;
+    use rand::rngs::SmallRng;
+
+    #[test]
+    fn apply_kraus() {
+        let mut rng = SmallRng::seed_from_u64(8);
+        let mut q = Qubit::from_theta_phi(0.7, 2.0);
+        let before = q.clone();
+        q.apply_kraus(&[Matrix2::identity()], &mut rng);
+        assert_relative_eq!(q, before, epsilon = 1e-6);
+
+        // Damping either leaves |1⟩ alone or drops it to |0⟩
+        let damping = amplitude_damping(0.3);
+        let trials = 4000;
+        let mut decays = 0;
+        for _ in 0..trials {
+            let mut q = Qubit::one();
+            q.apply_kraus(damping.kraus(), &mut rng);
+            if q == Qubit::zero() {
+                decays += 1;
+            } else {
+                assert_relative_eq!(q, Qubit::one(), epsilon = 1e-6);
+            }
+        }
+        // Within about four standard errors of 0.3
+        let rate = decays as Float / trials as Float;
+        assert!((rate - 0.3).abs() < 0.03, "decay rate {}", rate);
+        // |0⟩ never decays
+        let mut q = Qubit::zero();
+        q.apply_kraus(damping.kraus(), &mut rng);
+        assert_eq!(q, Qubit::zero());
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let q = Qubit::from_theta_phi(0.4, 1.1);
+        let json = serde_json::to_string(&q).unwrap();
+        assert_eq!(serde_json::from_str::<Qubit>(&json).unwrap(), q);
+        let json = serde_json::to_string(&Qubit::one()).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"re":0.0,"im":0.0},{"re":1.0,"im":0.0}]"#
+        );
+        let bad = r#"[{"re":1.0,"im":0.0},{"re":1.0,"im":0.0}]"#;
+        assert!(serde_json::from_str::<Qubit>(bad).is_err());
+    }
+
+    #[test]
+    fn bloch() {
+        let check = |q: Qubit, (x, y, z): (Float, Float, Float)| {
+            let (bx, by, bz) = q.bloch();
+            assert_relative_eq!(bx, x, epsilon = 1e-6);
+            assert_relative_eq!(by, y, epsilon = 1e-6);
+            assert_relative_eq!(bz, z, epsilon = 1e-6);
+        };
+        check(Qubit::zero(), (0.0, 0.0, 1.0));
+        check(Qubit::one(), (0.0, 0.0, -1.0));
+        check(Qubit::plus(), (1.0, 0.0, 0.0));
+        check(Qubit::minus(), (-1.0, 0.0, 0.0));
+        let (theta, phi) = ((1.1 as Float), (2.5 as Float));
+        check(
+            Qubit::from_theta_phi(theta, phi),
+            (
+                theta.sin() * phi.cos(),
+                theta.sin() * phi.sin(),
+                theta.cos(),
+            ),
+        );
+        // A global phase doesn't move the point
+        check(
+            Qubit::from_theta_phi_gamma(theta, phi, 0.8),
+            Qubit::from_theta_phi(theta, phi).bloch(),
+        );
+    }
+
+    #[test]
+    fn from_bloch() {
+        assert_relative_eq!(
+            Qubit::from_bloch(0.0, 0.0, 1.0),
+            &Qubit::zero()
+        );
+        assert_relative_eq!(
+            Qubit::from_bloch(1.0, 0.0, 0.0),
+            &Qubit::plus(),
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            Qubit::from_bloch(0.0, 0.0, -3.0),
+            &Qubit::one(),
+            epsilon = 1e-6
+        );
+        for &(x, y, z) in
+            &[(0.6, 0.0, 0.8), (-0.48, 0.6, -0.64), (0.0, -1.0, 0.0)]
+        {
+            let (bx, by, bz) = Qubit::from_bloch(x, y, z).bloch();
+            assert_relative_eq!(bx, x, epsilon = 1e-6);
+            assert_relative_eq!(by, y, epsilon = 1e-6);
+            assert_relative_eq!(bz, z, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn fidelity() {
+        assert_eq!(Qubit::zero().fidelity(&Qubit::one()), 0.0);
+        assert_relative_eq!(
+            Qubit::plus().fidelity(&Qubit::plus()),
+            1.0,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            Qubit::zero().fidelity(&Qubit::plus()),
+            0.5,
+            epsilon = 1e-6
+        );
+        // Global phase doesn't matter
+        assert_relative_eq!(
+            Qubit::one().fidelity(&-Qubit::one()),
+            1.0
+        );
+    }
+
+    #[test]
+    fn canonical() {
+        assert_relative_eq!(
+            (-Qubit::one()).canonical(),
+            Qubit::one().canonical()
+        );
+        assert_relative_eq!(
+            (-Qubit::one()).canonical(),
+            Qubit::one()
+        );
+        let q = Qubit::new(
+            Complex::new(0.0, 0.6),
+            Complex::new(-0.8, 0.0),
+        );
+        let c = q.canonical();
+        assert_relative_eq!(c, (-q).canonical());
+        assert_relative_eq!(c.inner[0], Complex::from_re(0.6));
+        assert_relative_eq!(c.inner[1], Complex::new(0.0, 0.8));
+    }
+
+    #[test]
+    fn random_qubits_are_valid() {
+        let mut rng = SmallRng::seed_from_u64(11);
+        let mut mean_z = 0.0;
+        for _ in 0..1000 {
+            let q = Qubit::random(&mut rng);
+            let norm: Float =
+                q.inner.iter().map(|x| x.mag_square()).sum();
+            assert_relative_eq!(norm, 1.0, epsilon = 1e-6);
+            mean_z += q.bloch().2 / 1000.0;
+        }
+        // The z coordinate is uniform on [-1, 1]
+        assert!(mean_z.abs() < 0.1, "Biased z: {}", mean_z);
+    }
+
+    #[test]
+    fn from_amplitudes() {
+        let q = Qubit::from_amplitudes(
+            Complex::from_re(3.0),
+            Complex::from_re(4.0),
+        );
+        assert_relative_eq!(
+            q,
+            &Qubit::new(Complex::from_re(0.6), Complex::from_re(0.8))
+        );
+        let (a, b) = (
+            Complex::from_re(FRAC_1_SQRT_2),
+            Complex::new(0.0, -FRAC_1_SQRT_2),
+        );
+        assert_eq!(Qubit::from_amplitudes(a, b), Qubit::new(a, b));
+        let nearly = Qubit::from_amplitudes(a * 1.00001, b);
+        assert_relative_eq!(
+            nearly.bias_zero(),
+            0.50001,
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "can't have all zero amplitudes")]
+    fn from_zero_amplitudes() {
+        Qubit::from_amplitudes(Complex::zero(), Complex::zero());
+    }
+
+    #[test]
+    fn normalize() {
+        let mut q = Qubit {
+            inner: Vector2::new(
+                Complex::new(3.0, 0.0),
+                Complex::new(0.0, 4.0),
+            ),
+        };
+        q.normalize();
+        assert_relative_eq!(
+            q,
+            &Qubit::new(
+                Complex::from_re(0.6),
+                Complex::new(0.0, 0.8)
+            )
+        );
+        let mut plus = Qubit::plus();
+        plus.normalize();
+        assert_relative_eq!(plus, &Qubit::plus());
+        let mut zero = Qubit {
+            inner: Vector2::zeros(),
+        };
+        zero.normalize();
+        assert_eq!(zero.inner, Vector2::zeros());
+    }
+
+    #[test]
+    fn thread_rng_sampling_is_fair() {
+        let zeros = (0..10_000)
+            .filter(|_| Qubit::plus().sample_is_zero())
+            .count();
+        assert!(4_700 < zeros && zeros < 5_300, "{} zeros", zeros);
+        assert!((0..100).all(|_| Qubit::one().sample_is_one()));
+    }
+
     #[test]
     fn fixed_vals_no_panic() {
         Qubit::plus();
@@ -146,6 +676,168 @@ mod tests {
         Qubit::new(Complex::zero(), -Complex::one());
     }
 
+    #[test]
+    fn sample_with_target() {
+        let targets = [0.0, 0.4999, 0.5001, 0.9999];
+        for &target in &targets {
+            assert!(!Qubit::zero().sample_with_target(target));
+            assert!(Qubit::one().sample_with_target(target));
+        }
+        let plus: Vec<_> = targets
+            .iter()
+            .map(|&t| Qubit::plus().sample_with_target(t))
+            .collect();
+        assert_eq!(plus, [false, false, true, true]);
+    }
+
+    #[test]
+    fn from_bits() {
+        assert_eq!(Qubit::from(true), Qubit::one());
+        assert_eq!(Qubit::from(false), Qubit::zero());
+        assert_eq!(Qubit::from(1u8), Qubit::one());
+        assert_eq!(Qubit::from(0u8), Qubit::zero());
+    }
+
+    #[test]
+    #[should_panic(expected = "A qubit basis state is 0 or 1, not 2")]
+    fn from_u8_out_of_range() {
+        let _ = Qubit::from(2u8);
+    }
+
+    #[test]
+    fn norm() {
+        assert_relative_eq!(
+            Qubit::plus().norm(),
+            1.0,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            Qubit::from_theta_phi(0.3, 2.0).norm_squared(),
+            1.0,
+            epsilon = 1e-6
+        );
+        let mut drifted = Qubit {
+            inner: Vector2::new(
+                Complex::new(3.0, 0.0),
+                Complex::new(0.0, 4.0),
+            ),
+        };
+        assert_relative_eq!(drifted.norm(), 5.0);
+        assert_relative_eq!(drifted.norm_squared(), 25.0);
+        drifted.normalize();
+        assert_relative_eq!(drifted.norm(), 1.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn bias_zero() {
+        assert_eq!(Qubit::zero().bias_zero(), 1.0);
+        assert_eq!(Qubit::one().bias_zero(), 0.0);
+        assert_relative_eq!(
+            Qubit::plus().bias_zero(),
+            0.5,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            Qubit::from_theta_phi(1.0, 0.0).bias_zero(),
+            0.5f64.cos().powi(2),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn sampled_expectation_converges() {
+        use crate::gates::unitary::gates::pauli;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let plus = Qubit::plus();
+        assert_relative_eq!(plus.expectation(&pauli::z()), 0.0);
+        let estimate =
+            plus.sampled_expectation(&pauli::z(), 10_000, &mut rng);
+        assert!(estimate.abs() < 0.05, "Estimated {}", estimate);
+        // Eigenstates always give the same outcome
+        let estimate =
+            plus.sampled_expectation(&pauli::x(), 100, &mut rng);
+        assert_relative_eq!(estimate, 1.0);
+    }
+
+    #[test]
+    fn sampled_expectation_measures_in_eigenbasis() {
+        use crate::consts::FRAC_PI_2;
+        use crate::gates::unitary::gates::{h, pauli};
+        let mut rng = SmallRng::seed_from_u64(1);
+        // |+i⟩ and |-i⟩ are eigenstates of Y
+        let plus_i = Qubit::from_theta_phi(FRAC_PI_2, FRAC_PI_2);
+        let minus_i = Qubit::from_theta_phi(FRAC_PI_2, -FRAC_PI_2);
+        assert_eq!(
+            plus_i.sampled_expectation(&pauli::y(), 50, &mut rng),
+            1.0
+        );
+        assert_eq!(
+            minus_i.sampled_expectation(&pauli::y(), 50, &mut rng),
+            -1.0
+        );
+        // Every shot is ±1, so one shot gives one of them
+        let one_shot =
+            Qubit::zero().sampled_expectation(&h(), 1, &mut rng);
+        assert!(one_shot == 1.0 || one_shot == -1.0);
+        let estimate =
+            Qubit::zero().sampled_expectation(&h(), 10_000, &mut rng);
+        assert!(
+            (estimate - FRAC_1_SQRT_2).abs() < 0.05,
+            "Estimated {}",
+            estimate
+        );
+        // ±I have a single eigenvalue
+        let minus_identity = UnaryGate::new(
+            Matrix2::identity() * Complex::from_re(-1.0),
+        );
+        let q = Qubit::from_theta_phi(0.7, 0.2);
+        assert_eq!(
+            q.sampled_expectation(
+                &UnaryGate::identity(),
+                10,
+                &mut rng
+            ),
+            1.0
+        );
+        assert_eq!(
+            q.sampled_expectation(&minus_identity, 10, &mut rng),
+            -1.0
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "An observable must be Hermitian")]
+    fn sampled_expectation_needs_hermitian() {
+        use crate::gates::unitary::gates::s;
+        let mut rng = SmallRng::seed_from_u64(0);
+        Qubit::plus().sampled_expectation(&s(), 10, &mut rng);
+    }
+
+    #[test]
+    #[should_panic(expected = "from 0 shots")]
+    fn sampled_expectation_needs_shots() {
+        use crate::gates::unitary::gates::pauli;
+        let mut rng = SmallRng::seed_from_u64(0);
+        Qubit::plus().sampled_expectation(&pauli::z(), 0, &mut rng);
+    }
+
+    #[test]
+    fn gram_matrix_of_basis_and_plus() {
+        let qubits = [Qubit::zero(), Qubit::one(), Qubit::plus()];
+        let gram = gram_matrix(&qubits);
+        let r = FRAC_1_SQRT_2;
+        let expected = [[1.0, 0.0, r], [0.0, 1.0, r], [r, r, 1.0]];
+        for i in 0..3 {
+            for j in 0..3 {
+                assert_relative_eq!(
+                    gram[i][j],
+                    Complex::from_re(expected[i][j])
+                );
+                assert_relative_eq!(gram[i][j], gram[j][i].conj());
+            }
+        }
+    }
+
     #[test]
     fn from_ang_for_any_vals() {
         Qubit::from_theta_phi(5.2, 542.23);
@@ -1,53 +1,284 @@
+use std::error::Error;
+use std::fmt;
 use std::iter::FromIterator;
 use std::iter::*;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct ClassicalRegister {
-    pub bits: u8,
+    pub bits: u64,
 }
 
-/// This will panic if the iterator has more that 64 elements
+/// The bits, least significant first.
+///
+/// Panics if there are more than 64; see
+/// [`try_from_iter`](ClassicalRegister::try_from_iter).
 impl FromIterator<bool> for ClassicalRegister {
     fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
-        let mut bits = 0;
-        for (n_bits, bit) in iter.into_iter().enumerate() {
-            assert!(
-                n_bits < 8,
-                "Got {} bits, but the register can only hold 8",
-                n_bits + 1
-            );
-            bits |= (bit as u8) << n_bits;
+        match Self::try_from_iter(iter) {
+            Ok(reg) => reg,
+            Err(e) => panic!("{}", e),
         }
-        Self { bits }
     }
 }
 
-impl From<u8> for ClassicalRegister {
-    fn from(bits: u8) -> Self {
+impl From<u64> for ClassicalRegister {
+    fn from(bits: u64) -> Self {
         Self { bits }
     }
 }
 
+/// Formats the bits in binary, most significant first. The width is
+/// the minimum number of digits, padding with zeros, so
+/// `format!("{:4}", reg)` gives `0101` for 5.
+impl fmt::Display for ClassicalRegister {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = f.width().unwrap_or(0);
+        write!(f, "{:0width$b}", self.bits, width = width)
+    }
+}
+
+/// The outcome of measuring every qubit of a register, which unlike a
+/// bare [`ClassicalRegister`] knows how many qubits there were.
+///
+/// Qubit 0 is the most significant bit, as in
+/// [`QuantumRegister`](super::quantum::QuantumRegister).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MeasurementOutcome {
+    pub bits: u64,
+    pub num_qubits: usize,
+}
+
+impl MeasurementOutcome {
+    /// Whether qubit `i` was measured as `|1⟩`.
+    ///
+    /// Panics if there's no qubit `i`.
+    pub fn qubit(&self, i: usize) -> bool {
+        assert!(
+            i < self.num_qubits,
+            "Qubit {} out of range for a {} qubit outcome",
+            i,
+            self.num_qubits
+        );
+        (self.bits >> (self.num_qubits - 1 - i)) & 1 == 1
+    }
+
+    /// Each qubit's value, starting from qubit 0
+    pub fn as_bools(&self) -> Vec<bool> {
+        (0..self.num_qubits).map(|i| self.qubit(i)).collect()
+    }
+}
+
+impl From<MeasurementOutcome> for ClassicalRegister {
+    fn from(outcome: MeasurementOutcome) -> Self {
+        Self { bits: outcome.bits }
+    }
+}
+
+/// Formats the bits in binary with one digit per qubit, qubit 0 first,
+/// so the outcome `|01⟩` is `01`
+impl fmt::Display for MeasurementOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:0width$b}", self.bits, width = self.num_qubits)
+    }
+}
+
+/// An index past the last bit of a [`ClassicalRegister`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfRange {
+    pub index: u8,
+}
+
+impl fmt::Display for OutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "bit {} out of range for a 64 bit classical register",
+            self.index
+        )
+    }
+}
+
+impl Error for OutOfRange {}
+
+/// More bits than fit in a [`ClassicalRegister`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooManyBits {
+    pub max: usize,
+}
+
+impl fmt::Display for TooManyBits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "got more than {} bits, but the register can only hold {}",
+            self.max, self.max
+        )
+    }
+}
+
+impl Error for TooManyBits {}
+
+/// The bits of a [`ClassicalRegister`], least significant first, up to
+/// the highest set bit
+#[derive(Debug, Clone)]
+pub struct Iter {
+    bits: u64,
+}
+
+impl Iterator for Iter {
+    type Item = bool;
+    fn next(&mut self) -> Option<bool> {
+        if self.bits == 0 {
+            return None;
+        }
+        let bit = self.bits & 1 == 1;
+        self.bits >>= 1;
+        Some(bit)
+    }
+}
+
+impl IntoIterator for &ClassicalRegister {
+    type Item = bool;
+    type IntoIter = Iter;
+    fn into_iter(self) -> Iter {
+        self.iter()
+    }
+}
+
 impl ClassicalRegister {
+    /// Collect bits, least significant first, failing if there are
+    /// more than 64. Nothing past the first bit that doesn't fit is
+    /// read, so this stops even on an endless iterator.
+    pub fn try_from_iter<I: IntoIterator<Item = bool>>(
+        iter: I,
+    ) -> Result<Self, TooManyBits> {
+        let mut bits = 0;
+        for (n_bits, bit) in iter.into_iter().enumerate() {
+            if n_bits == 64 {
+                return Err(TooManyBits { max: 64 });
+            }
+            bits |= (bit as u64) << n_bits;
+        }
+        Ok(Self { bits })
+    }
+
+    /// The bits, least significant first, stopping after the highest set
+    /// bit. Collecting them gives back the same register.
+    pub fn iter(&self) -> Iter {
+        Iter { bits: self.bits }
+    }
+
+    /// Bit `index`, counting from the least significant.
+    ///
+    /// Panics if `index` is out of range; [`get`](Self::get) is the
+    /// checked version.
     pub fn index(&self, index: u8) -> bool {
-        assert!(index < 8);
+        assert!(index < 64);
         ((self.bits >> index) & 1) == 1
     }
 
+    /// Set bit `index`, counting from the least significant.
+    ///
+    /// Panics if `index` is out of range; [`try_set`](Self::try_set) is
+    /// the checked version.
     pub fn set(&mut self, index: u8, val: bool) {
+        assert!(index < 64);
         if val {
             self.bits |= 1 << index;
         } else {
             self.bits &= !(1 << index);
         }
     }
+
+    /// Bit `index`, or `None` if it's out of range
+    pub fn get(&self, index: u8) -> Option<bool> {
+        if index < 64 {
+            Some(self.index(index))
+        } else {
+            None
+        }
+    }
+
+    /// Set bit `index`, failing if it's out of range
+    pub fn try_set(
+        &mut self,
+        index: u8,
+        val: bool,
+    ) -> Result<(), OutOfRange> {
+        if index >= 64 {
+            return Err(OutOfRange { index });
+        }
+        self.set(index, val);
+        Ok(())
+    }
+
+    /// Combine the outcomes of two independent registers, with `a` in
+    /// the low `a_width` bits and `b` above it.
+    ///
+    /// Panics if `a` doesn't fit in `a_width` bits, or the result
+    /// doesn't fit in the register.
+    pub fn concat(
+        a: &ClassicalRegister,
+        a_width: u8,
+        b: &ClassicalRegister,
+    ) -> ClassicalRegister {
+        assert!(a_width <= 64, "Registers are at most 64 bits wide");
+        assert!(
+            u128::from(a.bits) < 1 << a_width,
+            "{:#b} doesn't fit in {} bits",
+            a.bits,
+            a_width
+        );
+        let bits = u128::from(b.bits) << a_width | u128::from(a.bits);
+        assert!(
+            bits <= u64::MAX.into(),
+            "Concatenated register {:#b} doesn't fit in 64 bits",
+            bits
+        );
+        Self { bits: bits as u64 }
+    }
 }
 
 #[cfg(test)]
+// `repeat_n` needs Rust 1.82, and spelling out the bools reads better
+#[allow(clippy::manual_repeat_n, clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
     use std::iter::repeat;
 
+    #[test]
+    fn measurement_outcome() {
+        let outcome = MeasurementOutcome {
+            bits: 0b0010,
+            num_qubits: 4,
+        };
+        assert!(!outcome.qubit(0));
+        assert!(outcome.qubit(2));
+        assert_eq!(outcome.as_bools(), [false, false, true, false]);
+        assert_eq!(outcome.to_string(), "0010");
+        assert_eq!(ClassicalRegister::from(outcome).bits, 0b0010);
+        let one = MeasurementOutcome {
+            bits: 1,
+            num_qubits: 1,
+        };
+        assert_eq!(one.to_string(), "1");
+        assert_eq!(one.as_bools(), [true]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Qubit 4 out of range for a 4 qubit")]
+    fn measurement_outcome_qubit_out_of_range() {
+        MeasurementOutcome {
+            bits: 0,
+            num_qubits: 4,
+        }
+        .qubit(4);
+    }
+
     #[test]
     fn from_bit_array() {
         let x: ClassicalRegister =
@@ -62,7 +293,10 @@ mod tests {
         assert_eq!(y.bits, 0);
 
         let z: ClassicalRegister = repeat(true).take(8).collect();
-        assert_eq!(z.bits, std::u8::MAX);
+        assert_eq!(z.bits, 0xff);
+
+        let w: ClassicalRegister = repeat(true).take(64).collect();
+        assert_eq!(w.bits, u64::MAX);
 
         let a: ClassicalRegister = repeat(false).take(8).collect();
         assert_eq!(a.bits, 0);
@@ -70,10 +304,36 @@ mod tests {
 
     #[test]
     #[should_panic(
-        expected = "Got 9 bits, but the register can only hold 8"
+        expected = "got more than 64 bits, but the register can only hold 64"
     )]
     fn from_overfull_iter() {
-        let _ = repeat(true).take(9).collect::<ClassicalRegister>();
+        let _ = repeat(true).take(65).collect::<ClassicalRegister>();
+    }
+
+    #[test]
+    fn try_from_iter() {
+        assert_eq!(
+            ClassicalRegister::try_from_iter(repeat(true).take(64)),
+            Ok(ClassicalRegister { bits: u64::MAX })
+        );
+        assert_eq!(
+            ClassicalRegister::try_from_iter(repeat(false).take(65)),
+            Err(TooManyBits { max: 64 })
+        );
+        assert_eq!(
+            ClassicalRegister::try_from_iter(repeat(true).take(100)),
+            Err(TooManyBits { max: 64 })
+        );
+        assert_eq!(
+            ClassicalRegister::try_from_iter(repeat(true)),
+            Err(TooManyBits { max: 64 })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "got more than 64 bits")]
+    fn from_endless_iter() {
+        let _ = repeat(true).collect::<ClassicalRegister>();
     }
 
     #[test]
@@ -94,8 +354,8 @@ mod tests {
 
     #[test]
     #[should_panic]
-    fn index_8_panics() {
-        ClassicalRegister { bits: 8 }.index(8);
+    fn index_64_panics() {
+        ClassicalRegister { bits: 8 }.index(64);
     }
 
     #[test]
@@ -104,6 +364,92 @@ mod tests {
         ClassicalRegister { bits: 0 }.index(100);
     }
 
+    #[test]
+    #[should_panic]
+    fn set_64_panics() {
+        ClassicalRegister { bits: 0 }.set(64, true);
+    }
+
+    #[test]
+    fn display() {
+        let reg = ClassicalRegister { bits: 5 };
+        assert_eq!(format!("{:04}", reg), "0101");
+        assert_eq!(format!("{:4}", reg), "0101");
+        assert_eq!(format!("{}", reg), "101");
+        assert_eq!(format!("{:2}", reg), "101");
+        assert_eq!(format!("{}", ClassicalRegister { bits: 0 }), "0");
+        assert_eq!(
+            ClassicalRegister { bits: 0b0010110 }.to_string(),
+            "10110"
+        );
+    }
+
+    #[test]
+    fn get() {
+        let reg = ClassicalRegister {
+            bits: 1 << 63 | 0b10,
+        };
+        assert_eq!(reg.get(0), Some(false));
+        assert_eq!(reg.get(1), Some(true));
+        assert_eq!(reg.get(7), Some(false));
+        assert_eq!(reg.get(63), Some(true));
+        assert_eq!(reg.get(64), None);
+        assert_eq!(reg.get(255), None);
+    }
+
+    #[test]
+    fn try_set() {
+        let mut reg = ClassicalRegister { bits: 0 };
+        assert_eq!(reg.try_set(63, true), Ok(()));
+        assert_eq!(reg.try_set(2, true), Ok(()));
+        assert_eq!(reg.bits, 1 << 63 | 0b100);
+        assert_eq!(
+            reg.try_set(64, true),
+            Err(OutOfRange { index: 64 })
+        );
+        assert_eq!(reg.bits, 1 << 63 | 0b100);
+    }
+
+    #[test]
+    fn iter() {
+        let reg = ClassicalRegister { bits: 0b1010011 };
+        assert_eq!(
+            reg.iter().collect::<Vec<_>>(),
+            [true, true, false, false, true, false, true]
+        );
+        assert_eq!(reg.iter().collect::<ClassicalRegister>(), reg);
+        let max = ClassicalRegister { bits: u64::MAX };
+        assert_eq!((&max).into_iter().count(), 64);
+        assert_eq!(max.iter().collect::<ClassicalRegister>(), max);
+        let zero = ClassicalRegister { bits: 0 };
+        assert_eq!(zero.iter().next(), None);
+        for (i, bit) in (&reg).into_iter().enumerate() {
+            assert_eq!(bit, reg.index(i as u8));
+        }
+    }
+
+    #[test]
+    fn concat() {
+        let a = ClassicalRegister { bits: 0b10 };
+        let b = ClassicalRegister { bits: 0b01 };
+        assert_eq!(ClassicalRegister::concat(&a, 2, &b).bits, 0b0110);
+        assert_eq!(
+            ClassicalRegister::concat(&b, 3, &a).bits,
+            0b10001
+        );
+        assert_eq!(ClassicalRegister::concat(&a, 8, &0.into()), a);
+        let wide = ClassicalRegister::concat(&a, 40, &b);
+        assert_eq!(wide.bits, 1 << 40 | 0b10);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit in 64 bits")]
+    fn concat_overflow() {
+        let a = ClassicalRegister { bits: 0 };
+        let b = ClassicalRegister { bits: 0b11 };
+        ClassicalRegister::concat(&a, 63, &b);
+    }
+
     #[test]
     fn set_index() {
         let mut reg = ClassicalRegister { bits: 0 };
@@ -0,0 +1,126 @@
+//! Density matrices, for mixed states as well as pure ones
+use super::quantum::QuantumRegister;
+use crate::complex::Complex;
+use crate::Float;
+
+use nalgebra::allocator::Allocator;
+use nalgebra::default_allocator::DefaultAllocator;
+use nalgebra::dimension::DimName;
+use nalgebra::{Matrix2, MatrixN, U2};
+
+/// The density matrix `ρ` of a register with `N` states. Unlike a
+/// [`QuantumRegister`] this can describe a mixed state, such as one qubit
+/// of an entangled pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DensityMatrix<N: DimName>
+where
+    DefaultAllocator: Allocator<Complex, N, N>,
+{
+    pub(crate) mat: MatrixN<Complex, N>,
+}
+
+impl<N: DimName> DensityMatrix<N>
+where
+    DefaultAllocator:
+        Allocator<Complex, N, N> + Allocator<Complex, N>,
+{
+    /// The pure state `|ψ⟩⟨ψ|`
+    pub fn from_pure(reg: &QuantumRegister<N>) -> Self {
+        let psi = reg.clone().into_vector();
+        let mat = MatrixN::<Complex, N>::from_fn(|row, col| {
+            psi[row] * psi[col].conj()
+        });
+        Self { mat }
+    }
+
+    pub fn matrix(&self) -> &MatrixN<Complex, N> {
+        &self.mat
+    }
+
+    /// The sum of the diagonal, which is 1 for a valid density matrix
+    pub fn trace(&self) -> Complex {
+        (0..N::dim())
+            .fold(Complex::zero(), |acc, i| acc + self.mat[(i, i)])
+    }
+
+    /// `Tr(ρ²)`, which is 1 for a pure state and `1/N` for the maximally
+    /// mixed one
+    pub fn purity(&self) -> Float {
+        // ρ is hermitian, so Tr(ρ²) = Σ |ρ_ij|²
+        self.mat.iter().map(|x| x.mag_square()).sum()
+    }
+
+    /// The reduced density matrix of a single qubit, found by tracing out
+    /// every other qubit.
+    ///
+    /// Qubit 0 is the most significant bit of the basis state.
+    pub fn partial_trace(&self, qubit: usize) -> DensityMatrix<U2> {
+        let n_qubits = N::dim().trailing_zeros() as usize;
+        assert!(
+            qubit < n_qubits,
+            "Qubit {} out of range for a {} qubit register",
+            qubit,
+            n_qubits
+        );
+        let bit = 1 << (n_qubits - 1 - qubit);
+        let mut mat = Matrix2::from_element(Complex::zero());
+        for rest in (0..N::dim()).filter(|i| i & bit == 0) {
+            for a in 0..2 {
+                for b in 0..2 {
+                    mat[(a, b)] += self.mat
+                        [(rest | (a * bit), rest | (b * bit))];
+                }
+            }
+        }
+        DensityMatrix::<U2> { mat }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qubit::Qubit;
+    use approx::assert_relative_eq;
+    use nalgebra::{U4, U8};
+
+    #[test]
+    fn bell_state_halves_are_maximally_mixed() {
+        let bell =
+            DensityMatrix::from_pure(&QuantumRegister::<U4>::ghz());
+        assert_relative_eq!(bell.purity(), 1.0, epsilon = 1e-6);
+        assert_relative_eq!(
+            bell.trace(),
+            Complex::one(),
+            epsilon = 1e-6
+        );
+        for qubit in 0..2 {
+            let rho = bell.partial_trace(qubit);
+            assert_relative_eq!(
+                rho.mat,
+                Matrix2::identity() * Complex::from_re(0.5),
+                epsilon = 1e-6
+            );
+            assert_relative_eq!(rho.purity(), 0.5, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn partial_trace_matches_register() {
+        let reg = QuantumRegister::<U8>::w_state();
+        let rho = DensityMatrix::from_pure(&reg);
+        for qubit in 0..3 {
+            assert_relative_eq!(
+                rho.partial_trace(qubit).mat,
+                reg.partial_trace(qubit),
+                epsilon = 1e-6
+            );
+        }
+        // Tracing out an unentangled partner leaves a pure state
+        let product = QuantumRegister::<U4>::from_2_qubits(
+            Qubit::from_theta_phi(0.4, 1.3),
+            Qubit::minus(),
+        );
+        let rho = DensityMatrix::from_pure(&product).partial_trace(1);
+        assert_relative_eq!(rho.purity(), 1.0, epsilon = 1e-6);
+    }
+}
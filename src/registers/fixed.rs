@@ -0,0 +1,152 @@
+//! A register sized by a const generic, rather than a typenum dimension
+use super::classical::ClassicalRegister;
+use super::quantum::collapse_amplitudes;
+use crate::complex::Complex;
+use crate::gates::binary::BinaryGate;
+use crate::gates::unitary::UnaryGate;
+use crate::Float;
+
+use rand::Rng;
+
+/// A quantum register with `N` states, so `log2(N)` qubits. This is the
+/// same as a [`QuantumRegister`](super::quantum::QuantumRegister), but
+/// its size is a plain number, as in `Register<8>`, so it needs no
+/// typenum constants or nalgebra allocator bounds.
+///
+/// Qubit 0 is the most significant bit of the basis state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Register<const N: usize> {
+    amps: [Complex; N],
+}
+
+impl<const N: usize> Register<N> {
+    /// The classical state `cr` as a basis state.
+    ///
+    /// Panics if `N` isn't a power of two, or `cr` doesn't fit.
+    pub fn from_classical(cr: ClassicalRegister) -> Self {
+        assert!(
+            N.is_power_of_two(),
+            "A register needs a power of two states, not {}",
+            N
+        );
+        let state = cr.bits as usize;
+        assert!(
+            state < N,
+            "Basis state {} out of range for a {} state register",
+            state,
+            N
+        );
+        let mut amps = [Complex::zero(); N];
+        amps[state] = Complex::one();
+        Register { amps }
+    }
+
+    /// The amplitude of each basis state, in order
+    pub fn amplitudes(&self) -> &[Complex; N] {
+        &self.amps
+    }
+
+    pub fn collapse(&self) -> ClassicalRegister {
+        self.collapse_with_rng(&mut rand::thread_rng())
+    }
+
+    /// [`collapse`](Self::collapse), drawing randomness from `rng`
+    pub fn collapse_with_rng<R: Rng>(
+        &self,
+        rng: &mut R,
+    ) -> ClassicalRegister {
+        collapse_amplitudes(&self.amps, rng.gen::<Float>())
+    }
+
+    /// The probability of measuring each basis state, in order
+    pub fn probabilities(&self) -> [Float; N] {
+        let mut probs = [0.0; N];
+        for (p, amp) in probs.iter_mut().zip(self.amps.iter()) {
+            *p = amp.mag_square();
+        }
+        probs
+    }
+
+    /// Apply `gate` to qubit `target`
+    pub fn apply_unary(
+        &self,
+        gate: &UnaryGate,
+        target: usize,
+    ) -> Self {
+        let mut amps = self.amps;
+        gate.apply_to_slice(&mut amps, target);
+        Register { amps }
+    }
+
+    /// Apply `gate` to qubits `first` and `second`, where `first` is the
+    /// gate's high-order qubit
+    pub fn apply_binary(
+        &self,
+        gate: &BinaryGate,
+        first: usize,
+        second: usize,
+    ) -> Self {
+        let mut amps = self.amps;
+        gate.apply_to_slice(&mut amps, first, second);
+        Register { amps }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gates::binary::gates::cnot;
+    use crate::gates::unitary::gates::h;
+    use crate::registers::quantum::QuantumRegister;
+    use approx::assert_relative_eq;
+    use nalgebra::U8;
+
+    #[test]
+    fn from_classical() {
+        fn round_trip<const N: usize>() {
+            for i in 0..N as u64 {
+                let reg = Register::<N>::from_classical(i.into());
+                assert_eq!(reg.collapse(), i.into());
+            }
+        }
+        round_trip::<2>();
+        round_trip::<16>();
+        round_trip::<256>();
+    }
+
+    #[test]
+    fn bell_state() {
+        let reg = Register::<4>::from_classical(0.into())
+            .apply_unary(&h(), 0)
+            .apply_binary(&cnot(), 0, 1);
+        let probs = reg.probabilities();
+        assert_relative_eq!(probs[0b00], 0.5, epsilon = 1e-6);
+        assert_relative_eq!(probs[0b11], 0.5, epsilon = 1e-6);
+        for _ in 0..100 {
+            let bits = reg.collapse().bits;
+            assert!(bits == 0b00 || bits == 0b11);
+        }
+    }
+
+    #[test]
+    fn matches_quantum_register() {
+        let reg = Register::<8>::from_classical(0b011.into())
+            .apply_unary(&h(), 2)
+            .apply_binary(&cnot(), 2, 0);
+        let expected = cnot().apply_to(
+            h().apply_to(QuantumRegister::<U8>::basis(0b011), 2),
+            2,
+            0,
+        );
+        assert_eq!(
+            reg.amplitudes(),
+            expected.into_vector().as_slice()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "power of two")]
+    fn size_must_be_a_power_of_two() {
+        Register::<6>::from_classical(0.into());
+    }
+}
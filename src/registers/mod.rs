@@ -1,2 +1,96 @@
 pub mod classical;
+pub mod density;
+pub mod fixed;
 pub mod quantum;
+pub mod traced;
+
+pub use fixed::Register;
+
+use crate::complex::Complex;
+
+use nalgebra::allocator::Allocator;
+use nalgebra::default_allocator::DefaultAllocator;
+use nalgebra::dimension::{Dim, DimMul, DimName, DimProd};
+use nalgebra::{MatrixN, VectorN};
+
+/// The Kronecker product `a ⊗ b` of two state vectors. `a` gives the
+/// high-order qubits, like the first qubit passed to
+/// [`from_2_qubits`](quantum::QuantumRegister::from_2_qubits).
+pub(crate) fn kron_vec<A, B>(
+    a: &VectorN<Complex, A>,
+    b: &VectorN<Complex, B>,
+) -> VectorN<Complex, DimProd<A, B>>
+where
+    A: DimName + DimMul<B>,
+    B: DimName,
+    DimProd<A, B>: DimName,
+    DefaultAllocator: Allocator<Complex, A>
+        + Allocator<Complex, B>
+        + Allocator<Complex, DimProd<A, B>>,
+{
+    a.kronecker(b)
+}
+
+/// The Kronecker product `a ⊗ b` of two operators, with the same
+/// ordering as [`kron_vec`]: `a` acts on the high-order qubits. The
+/// dimensions can be [`Dynamic`](nalgebra::Dynamic) too.
+pub(crate) fn kron_mat<A, B>(
+    a: &MatrixN<Complex, A>,
+    b: &MatrixN<Complex, B>,
+) -> MatrixN<Complex, DimProd<A, B>>
+where
+    A: Dim + DimMul<B>,
+    B: Dim,
+    DefaultAllocator: Allocator<Complex, A, A>
+        + Allocator<Complex, B, B>
+        + Allocator<Complex, DimProd<A, B>, DimProd<A, B>>,
+{
+    a.kronecker(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gates::unitary::gates::{h, not};
+    use crate::qubit::Qubit;
+    use quantum::QuantumRegister;
+
+    use approx::assert_relative_eq;
+    use nalgebra::U4;
+
+    #[test]
+    fn kron_vec_matches_from_2_qubits() {
+        let qubits = [
+            Qubit::zero(),
+            Qubit::one(),
+            Qubit::plus(),
+            Qubit::minus(),
+            Qubit::from_theta_phi(0.7, 2.1),
+        ];
+        for a in &qubits {
+            for b in &qubits {
+                let reg = QuantumRegister::<U4>::from_2_qubits(
+                    a.clone(),
+                    b.clone(),
+                );
+                assert_relative_eq!(
+                    kron_vec(&a.inner, &b.inner),
+                    reg.into_vector(),
+                    epsilon = 1e-6
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn kron_mat_acts_on_kron_vec() {
+        let (a, b) =
+            (Qubit::from_theta_phi(0.3, 1.0), Qubit::minus());
+        let (x, y) = (not(), h());
+        assert_relative_eq!(
+            kron_mat(&x.mat, &y.mat) * kron_vec(&a.inner, &b.inner),
+            kron_vec(&x.run(a).inner, &y.run(b).inner),
+            epsilon = 1e-6
+        );
+    }
+}
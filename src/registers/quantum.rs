@@ -1,17 +1,80 @@
-use std::convert::TryInto;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
 
-use rand;
+use rand::{self, Rng};
 
-use super::classical::ClassicalRegister;
+use super::classical::{ClassicalRegister, MeasurementOutcome};
+use super::kron_vec;
+use crate::circuit::{Circuit, Operation};
 use crate::complex::Complex;
+use crate::gates::binary::gates::cnot;
+use crate::gates::binary::BinaryGate;
+use crate::gates::unitary::gates::{h, s};
+use crate::gates::unitary::UnaryGate;
+use crate::linalg;
 use crate::qubit::Qubit;
+use crate::sample::SampleSource;
+use crate::Float;
 use nalgebra::allocator::Allocator;
 use nalgebra::default_allocator::DefaultAllocator;
 use nalgebra::dimension::DimName;
 use nalgebra::dimension::*;
+use nalgebra::Matrix2;
+use nalgebra::Vector2;
 use nalgebra::Vector4;
 use nalgebra::VectorN;
 
+/// Errors from building a [`QuantumRegister`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterError {
+    /// The basis state doesn't exist in a register with this many states
+    OutOfRange { state: usize, states: usize },
+    /// The number of amplitudes doesn't match the number of states
+    WrongLength { expected: usize, got: usize },
+    /// Every amplitude was zero, so the state can't be normalized
+    ZeroVector,
+    /// The probabilities of the amplitudes don't add up to 1
+    NotNormalized,
+}
+
+impl fmt::Display for RegisterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegisterError::OutOfRange { state, states } => write!(
+                f,
+                "Basis state {} out of range for a {} state register",
+                state, states
+            ),
+            RegisterError::WrongLength { expected, got } => write!(
+                f,
+                "expected {} amplitudes, got {}",
+                expected, got
+            ),
+            RegisterError::ZeroVector => {
+                write!(f, "can't normalize the zero vector")
+            }
+            RegisterError::NotNormalized => {
+                write!(f, "amplitudes aren't normalized")
+            }
+        }
+    }
+}
+
+impl Error for RegisterError {}
+
+/// A basis to measure qubits in. Outcome 0 is the +1 eigenstate of the
+/// matching Pauli operator, such as `|+⟩` for `X`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Basis {
+    /// The computational basis, `|0⟩` and `|1⟩`
+    Z,
+    /// `|+⟩` and `|-⟩`
+    X,
+    /// `|+i⟩` and `|-i⟩`
+    Y,
+}
+
 /// `N` is the number of states = 2**num_qubits
 #[derive(Clone, PartialEq, Debug)]
 pub struct QuantumRegister<N: DimName>
@@ -21,101 +84,1962 @@ where
     qubits: VectorN<Complex, N>,
 }
 
-impl<N: nalgebra::dimension::DimName> QuantumRegister<N>
-where
-    DefaultAllocator: Allocator<Complex, N>,
-{
-    pub fn from_classical(cr: ClassicalRegister) -> Self {
-        let mut qubits =
-            nalgebra::VectorN::<Complex, N>::from_element(
-                Complex::zero(),
+impl<N: nalgebra::dimension::DimName> QuantumRegister<N>
+where
+    DefaultAllocator: Allocator<Complex, N>,
+{
+    /// Load a classical register as a basis state.
+    ///
+    /// Panics if its value doesn't fit in a register of this width; see
+    /// [`try_from_classical`](Self::try_from_classical).
+    pub fn from_classical(cr: ClassicalRegister) -> Self {
+        match Self::try_from_classical(cr) {
+            Ok(reg) => reg,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// The computational basis state `|index⟩`.
+    ///
+    /// Panics if `index` is out of range.
+    pub fn basis(index: usize) -> Self {
+        assert!(
+            index < N::dim(),
+            "Basis state {} out of range for a {} state register",
+            index,
+            N::dim()
+        );
+        let mut qubits =
+            VectorN::<Complex, N>::from_element(Complex::zero());
+        qubits[index] = Complex::one();
+        QuantumRegister { qubits }
+    }
+
+    /// Load a classical register as a basis state, checking that its
+    /// value fits in a register of this width.
+    pub fn try_from_classical(
+        cr: ClassicalRegister,
+    ) -> Result<Self, RegisterError> {
+        let state = cr.bits as usize;
+        if state >= N::dim() {
+            return Err(RegisterError::OutOfRange {
+                state,
+                states: N::dim(),
+            });
+        }
+        Ok(Self::basis(state))
+    }
+
+    /// The GHZ state `(|0…0⟩ + |1…1⟩)/√2`
+    pub fn ghz() -> Self {
+        let mut qubits =
+            VectorN::<Complex, N>::from_element(Complex::zero());
+        qubits[0] = crate::consts::FRAC_1_SQRT_2.into();
+        qubits[N::dim() - 1] = crate::consts::FRAC_1_SQRT_2.into();
+        debug_assert!(Self::is_valid(&qubits));
+        QuantumRegister { qubits }
+    }
+
+    /// The uniform superposition of every basis state, `1/√N Σ|k⟩`
+    pub fn uniform() -> Self {
+        let amp = (N::dim() as Float).sqrt().recip();
+        let qubits = VectorN::<Complex, N>::from_element(
+            Complex::from_re(amp),
+        );
+        debug_assert!(Self::is_valid(&qubits));
+        QuantumRegister { qubits }
+    }
+
+    /// The equal superposition `1/√k Σ|s⟩` of the `k` distinct basis
+    /// states in `states`. Repeated states only count once.
+    ///
+    /// Panics if `states` is empty or any state is out of range.
+    pub fn superposition(states: &[usize]) -> Self {
+        assert!(
+            !states.is_empty(),
+            "A superposition needs at least one basis state"
+        );
+        let mut qubits = VectorN::<Complex, N>::zeros();
+        for &state in states {
+            assert!(
+                state < N::dim(),
+                "Basis state {} out of range for a {} state register",
+                state,
+                N::dim()
+            );
+            qubits[state] = Complex::one();
+        }
+        let k = qubits
+            .iter()
+            .filter(|&&amp| amp != Complex::zero())
+            .count();
+        qubits *= Complex::from_re((k as Float).sqrt().recip());
+        debug_assert!(Self::is_valid(&qubits));
+        QuantumRegister { qubits }
+    }
+
+    /// Amplitude-encode `data`: normalize it to unit length and use it
+    /// as the (real) amplitudes of the register.
+    pub fn amplitude_encode(
+        data: &[Float],
+    ) -> Result<Self, RegisterError> {
+        if data.len() != N::dim() {
+            return Err(RegisterError::WrongLength {
+                expected: N::dim(),
+                got: data.len(),
+            });
+        }
+        let norm = data.iter().map(|x| x * x).sum::<Float>().sqrt();
+        if norm == 0.0 {
+            return Err(RegisterError::ZeroVector);
+        }
+        let qubits = VectorN::<Complex, N>::from_iterator(
+            data.iter().map(|&x| Complex::from_re(x / norm)),
+        );
+        debug_assert!(Self::is_valid(&qubits));
+        Ok(QuantumRegister { qubits })
+    }
+
+    /// Use `amps` as the amplitudes of a register, checking there's one
+    /// per state and they're normalized
+    pub fn from_array<const M: usize>(
+        amps: [Complex; M],
+    ) -> Result<Self, RegisterError> {
+        if M != N::dim() {
+            return Err(RegisterError::WrongLength {
+                expected: N::dim(),
+                got: M,
+            });
+        }
+        Self::try_from_vector(VectorN::<Complex, N>::from_iterator(
+            amps.iter().copied(),
+        ))
+    }
+
+    /// The amplitude of each basis state, in order
+    pub fn amplitudes(&self) -> &[Complex] {
+        self.qubits.as_slice()
+    }
+
+    /// The sum of the probabilities of every basis state, which is 1
+    /// for a valid register
+    pub fn total_probability(&self) -> Float {
+        self.qubits.iter().map(|x| x.mag_square()).sum()
+    }
+
+    /// Whether the probabilities still add up to 1, to within `1e-6`.
+    /// Unitary gates keep this true, so a `false` points to a register
+    /// built with [`from_vector_unchecked`](Self::from_vector_unchecked)
+    /// or a non-unitary operation.
+    #[must_use]
+    pub fn is_valid_state(&self) -> bool {
+        Self::is_valid(&self.qubits)
+    }
+
+    #[must_use]
+    fn is_valid(vector: &VectorN<Complex, N>) -> bool {
+        let mut acc = 0.0;
+        for i in vector.iter() {
+            acc += i.mag_square()
+        }
+        (acc - 1.0).abs() <= 1.0e-6
+    }
+
+    // Target should be a random float in [0, 1). Used for edge case
+    // tests.
+    fn collapse_with_target(
+        &self,
+        target: Float,
+    ) -> ClassicalRegister {
+        collapse_amplitudes(self.qubits.as_slice(), target)
+    }
+
+    pub fn collapse(&self) -> ClassicalRegister {
+        self.collapse_with_rng(&mut rand::thread_rng())
+    }
+
+    /// [`collapse`](Self::collapse), drawing randomness from `rng`
+    pub fn collapse_with_rng<R: Rng>(
+        &self,
+        rng: &mut R,
+    ) -> ClassicalRegister {
+        self.collapse_with_source(rng)
+    }
+
+    /// [`collapse`](Self::collapse), picking the outcome with the next
+    /// number from `source`. Each state owns a slice of `[0, 1)` as long
+    /// as its probability, in order, so a source that always gives 0
+    /// always picks the first possible state.
+    pub fn collapse_with_source<S: SampleSource + ?Sized>(
+        &self,
+        source: &mut S,
+    ) -> ClassicalRegister {
+        self.collapse_with_target(source.next_unit())
+    }
+
+    /// Collapse the register, also returning the probability of the
+    /// state it collapsed to
+    pub fn collapse_with_prob(&self) -> (ClassicalRegister, Float) {
+        self.collapse_with_prob_with_rng(&mut rand::thread_rng())
+    }
+
+    /// [`collapse_with_prob`](Self::collapse_with_prob), drawing
+    /// randomness from `rng`
+    pub fn collapse_with_prob_with_rng<R: Rng>(
+        &self,
+        rng: &mut R,
+    ) -> (ClassicalRegister, Float) {
+        let outcome = self.collapse_with_rng(rng);
+        let prob = self.qubits[outcome.bits as usize].mag_square();
+        (outcome, prob)
+    }
+
+    /// Collapse the register `shots` times, counting how often each
+    /// basis state comes up. The result is indexed by basis state.
+    pub fn sample_counts(&self, shots: usize) -> Vec<usize> {
+        self.sample_counts_with_rng(shots, &mut rand::thread_rng())
+    }
+
+    /// [`sample_counts`](Self::sample_counts), drawing randomness from
+    /// `rng`
+    pub fn sample_counts_with_rng<R: Rng>(
+        &self,
+        shots: usize,
+        rng: &mut R,
+    ) -> Vec<usize> {
+        let mut counts = vec![0; N::dim()];
+        for _ in 0..shots {
+            counts[self.collapse_with_rng(rng).bits as usize] += 1;
+        }
+        counts
+    }
+
+    /// Collapse the register `shots` times, giving each outcome in
+    /// order, for when the order matters or a histogram from
+    /// [`sample_counts`](Self::sample_counts) isn't enough
+    pub fn sample(&self, shots: usize) -> Vec<ClassicalRegister> {
+        self.sample_with_rng(shots, &mut rand::thread_rng())
+    }
+
+    /// [`sample`](Self::sample), drawing randomness from `rng`
+    pub fn sample_with_rng<R: Rng>(
+        &self,
+        shots: usize,
+        rng: &mut R,
+    ) -> Vec<ClassicalRegister> {
+        (0..shots).map(|_| self.collapse_with_rng(rng)).collect()
+    }
+
+    /// Estimate the probability of measuring `state` from `shots`
+    /// collapses, returning the observed frequency and its standard
+    /// error `√(p(1-p)/shots)`.
+    ///
+    /// Panics if `shots` is 0.
+    pub fn estimate_probability(
+        &self,
+        state: usize,
+        shots: usize,
+    ) -> (Float, Float) {
+        self.estimate_probability_with_rng(
+            state,
+            shots,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    /// [`estimate_probability`](Self::estimate_probability), drawing
+    /// randomness from `rng`
+    pub fn estimate_probability_with_rng<R: Rng>(
+        &self,
+        state: usize,
+        shots: usize,
+        rng: &mut R,
+    ) -> (Float, Float) {
+        assert!(
+            shots > 0,
+            "Can't estimate a probability from 0 shots"
+        );
+        let hits = self.sample_counts_with_rng(shots, rng)[state];
+        let p = hits as Float / shots as Float;
+        (p, (p * (1.0 - p) / shots as Float).sqrt())
+    }
+
+    /// Measure every qubit in `basis`, rather than the computational
+    /// basis
+    pub fn collapse_in_basis(
+        &self,
+        basis: Basis,
+    ) -> ClassicalRegister {
+        self.collapse_in_basis_with_rng(
+            basis,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    /// [`collapse_in_basis`](Self::collapse_in_basis), drawing
+    /// randomness from `rng`
+    pub fn collapse_in_basis_with_rng<R: Rng>(
+        &self,
+        basis: Basis,
+        rng: &mut R,
+    ) -> ClassicalRegister {
+        // Rotate the eigenstates of the basis onto |0⟩ and |1⟩
+        let change = match basis {
+            Basis::Z => return self.collapse_with_rng(rng),
+            Basis::X => h(),
+            Basis::Y => h().compose(&s().dagger()),
+        };
+        let mut qubits = self.qubits.clone();
+        for target in 0..self.num_qubits() {
+            change.apply_to_slice(qubits.as_mut_slice(), target);
+        }
+        QuantumRegister { qubits }.collapse_with_rng(rng)
+    }
+
+    /// Measure every qubit, leaving the register in the basis state it
+    /// collapsed to, so measuring again gives the same outcome.
+    pub fn measure(&mut self) -> ClassicalRegister {
+        self.measure_with_rng(&mut rand::thread_rng())
+    }
+
+    /// [`measure`](Self::measure), drawing randomness from `rng`
+    pub fn measure_with_rng<R: Rng>(
+        &mut self,
+        rng: &mut R,
+    ) -> ClassicalRegister {
+        let outcome = self.collapse_with_rng(rng);
+        *self = Self::basis(outcome.bits as usize);
+        outcome
+    }
+
+    /// [`collapse`](Self::collapse), as an outcome that knows the
+    /// register has [`num_qubits`](Self::num_qubits) qubits
+    pub fn collapse_outcome(&self) -> MeasurementOutcome {
+        self.collapse_outcome_with_rng(&mut rand::thread_rng())
+    }
+
+    /// [`collapse_outcome`](Self::collapse_outcome), drawing randomness
+    /// from `rng`
+    pub fn collapse_outcome_with_rng<R: Rng>(
+        &self,
+        rng: &mut R,
+    ) -> MeasurementOutcome {
+        MeasurementOutcome {
+            bits: self.collapse_with_rng(rng).bits,
+            num_qubits: self.num_qubits(),
+        }
+    }
+
+    /// [`measure`](Self::measure), as an outcome that knows the
+    /// register has [`num_qubits`](Self::num_qubits) qubits
+    pub fn measure_outcome(&mut self) -> MeasurementOutcome {
+        self.measure_outcome_with_rng(&mut rand::thread_rng())
+    }
+
+    /// [`measure_outcome`](Self::measure_outcome), drawing randomness
+    /// from `rng`
+    pub fn measure_outcome_with_rng<R: Rng>(
+        &mut self,
+        rng: &mut R,
+    ) -> MeasurementOutcome {
+        MeasurementOutcome {
+            bits: self.measure_with_rng(rng).bits,
+            num_qubits: self.num_qubits(),
+        }
+    }
+
+    /// The probability that measuring qubit `qubit` gives `|1⟩`, without
+    /// measuring it.
+    ///
+    /// Qubit 0 is the most significant bit of the basis state.
+    pub fn marginal_probability(&self, qubit: usize) -> Float {
+        let n_qubits = Self::qubit_count();
+        assert!(
+            qubit < n_qubits,
+            "Qubit {} out of range for a {} qubit register",
+            qubit,
+            n_qubits
+        );
+        let bit = 1 << (n_qubits - 1 - qubit);
+        self.qubits
+            .iter()
+            .enumerate()
+            .filter(|(state, _)| state & bit != 0)
+            .map(|(_, amp)| amp.mag_square())
+            .sum()
+    }
+
+    /// Measure a single qubit, returning true for `|1⟩`, and collapse the
+    /// rest of the register to the state consistent with the outcome.
+    ///
+    /// Qubit 0 is the most significant bit of the basis state.
+    pub fn measure_qubit(&mut self, index: usize) -> bool {
+        self.measure_qubit_with_rng(index, &mut rand::thread_rng())
+    }
+
+    /// [`measure_qubit`](Self::measure_qubit), drawing randomness from
+    /// `rng`
+    pub fn measure_qubit_with_rng<R: Rng>(
+        &mut self,
+        index: usize,
+        rng: &mut R,
+    ) -> bool {
+        let p_one = self.marginal_probability(index);
+        let bit = 1 << (Self::qubit_count() - 1 - index);
+        let one = rng.gen::<Float>() < p_one;
+        let p = if one { p_one } else { 1.0 - p_one };
+        let scale = p.sqrt().recip();
+        for (state, amp) in self.qubits.iter_mut().enumerate() {
+            if (state & bit != 0) == one {
+                *amp = *amp * scale;
+            } else {
+                *amp = Complex::zero();
+            }
+        }
+        one
+    }
+
+    /// Scale the amplitudes back to unit length, undoing drift from
+    /// rounding or slightly non-unitary operations. The zero vector is
+    /// left as it is.
+    pub fn normalize(&mut self) {
+        let norm =
+            self.qubits.iter().map(|x| x.mag_square()).sum::<Float>();
+        if norm != 0.0 {
+            self.qubits /= Complex::from_re(norm.sqrt());
+        }
+    }
+
+    /// Multiply every amplitude by `e^{iθ}`. This changes no measurement
+    /// on its own, only interference with another branch.
+    pub fn apply_global_phase(&mut self, theta: Float) {
+        self.qubits *= Complex::exp_ix(theta);
+    }
+
+    /// Replace each amplitude `a_i` with `f(i, a_i)`. The result isn't
+    /// renormalized, so for anything but a change of phase follow this
+    /// with [`normalize`](Self::normalize).
+    pub fn map_amplitudes(
+        &mut self,
+        f: impl Fn(usize, Complex) -> Complex,
+    ) {
+        for (i, amp) in self.qubits.iter_mut().enumerate() {
+            *amp = f(i, *amp);
+        }
+    }
+
+    /// Reverse the order of the qubits, so qubit 0 becomes the least
+    /// significant bit of the basis state rather than the most. This
+    /// converts to and from the opposite convention.
+    pub fn reverse_qubits(&mut self) {
+        let n_qubits = self.num_qubits();
+        for state in 0..self.num_states() {
+            let reversed = (0..n_qubits)
+                .filter(|bit| state & (1 << bit) != 0)
+                .fold(0, |acc, bit| {
+                    acc | (1 << (n_qubits - 1 - bit))
+                });
+            if state < reversed {
+                self.qubits.swap_rows(state, reversed);
+            }
+        }
+    }
+
+    /// The register with amplitude `f(i)` for each basis state `i`,
+    /// scaled to unit length.
+    ///
+    /// Panics if every amplitude is zero.
+    pub fn from_fn(f: impl Fn(usize) -> Complex) -> Self {
+        let mut reg = Self {
+            qubits: VectorN::<Complex, N>::from_fn(|i, _| f(i)),
+        };
+        assert!(
+            reg.qubits.iter().any(|amp| *amp != Complex::zero()),
+            "A register needs a nonzero amplitude"
+        );
+        reg.normalize();
+        reg
+    }
+
+    /// Use `qubits` as the amplitudes of a register.
+    ///
+    /// Panics if they aren't normalized.
+    pub fn from_vector(qubits: VectorN<Complex, N>) -> Self {
+        Self::try_from_vector(qubits)
+            .expect("Invalid quantum register amplitudes")
+    }
+
+    /// Use `qubits` as the amplitudes of a register, checking they're
+    /// normalized.
+    pub fn try_from_vector(
+        qubits: VectorN<Complex, N>,
+    ) -> Result<Self, RegisterError> {
+        if Self::is_valid(&qubits) {
+            Ok(Self { qubits })
+        } else {
+            Err(RegisterError::NotNormalized)
+        }
+    }
+
+    /// Use `qubits` as the amplitudes of a register without checking
+    /// them. Gates use this, since they can't change the norm beyond
+    /// rounding error.
+    pub fn from_vector_unchecked(
+        qubits: VectorN<Complex, N>,
+    ) -> Self {
+        Self { qubits }
+    }
+
+    pub fn into_vector(self) -> VectorN<Complex, N> {
+        self.qubits
+    }
+
+    /// Apply `gate` to qubit `target`.
+    ///
+    /// Qubit 0 is the most significant bit of the basis state, so it's
+    /// the first qubit passed to [`from_2_qubits`](Self::from_2_qubits).
+    pub fn apply_unary(
+        &self,
+        gate: &UnaryGate,
+        target: usize,
+    ) -> Self {
+        let mut qubits = self.qubits.clone();
+        gate.apply_to_slice(qubits.as_mut_slice(), target);
+        Self { qubits }
+    }
+
+    /// Apply `gate` to qubit `target` if bit `bit` of `classical` is set,
+    /// as for the corrections in teleportation
+    pub fn apply_if(
+        &mut self,
+        gate: &UnaryGate,
+        target: usize,
+        classical: &ClassicalRegister,
+        bit: u8,
+    ) {
+        if classical.index(bit) {
+            self.apply_unary_in_place(gate, target);
+        }
+    }
+
+    /// [`apply_unary`](Self::apply_unary), without copying the register
+    pub fn apply_unary_in_place(
+        &mut self,
+        gate: &UnaryGate,
+        target: usize,
+    ) {
+        gate.apply_to_slice(self.qubits.as_mut_slice(), target);
+    }
+
+    /// [`apply_unary`](Self::apply_unary), also describing the
+    /// operation, eg `"H on qubit 1"`.
+    pub fn apply_unary_traced(
+        &self,
+        gate: &UnaryGate,
+        target: usize,
+    ) -> (Self, String) {
+        let description = format!(
+            "{} on qubit {}",
+            gate.name().unwrap_or("U"),
+            target
+        );
+        (self.apply_unary(gate, target), description)
+    }
+
+    /// The reduced density matrix of a single qubit, found by tracing out
+    /// every other qubit.
+    pub fn partial_trace(&self, qubit: usize) -> Matrix2<Complex> {
+        let n_qubits = Self::qubit_count();
+        assert!(
+            qubit < n_qubits,
+            "Qubit {} out of range for a {} qubit register",
+            qubit,
+            n_qubits
+        );
+        let bit = 1 << (n_qubits - 1 - qubit);
+        let mut rho = [[Complex::zero(); 2]; 2];
+        for i in (0..N::dim()).filter(|i| i & bit == 0) {
+            let amps = [self.qubits[i], self.qubits[i | bit]];
+            for (row, a) in rho.iter_mut().zip(&amps) {
+                for (entry, b) in row.iter_mut().zip(&amps) {
+                    *entry += *a * b.conj();
+                }
+            }
+        }
+        Matrix2::new(rho[0][0], rho[0][1], rho[1][0], rho[1][1])
+    }
+
+    /// The state of qubit `index`, if it isn't entangled with the rest
+    /// of the register. The global phase is lost, so this is in
+    /// [`canonical`](Qubit::canonical) form. After measuring every other
+    /// qubit, this is always `Some`.
+    pub fn extract_qubit(&self, index: usize) -> Option<Qubit> {
+        let rho = self.partial_trace(index);
+        let purity: Float = rho.iter().map(|x| x.mag_square()).sum();
+        if purity < 1.0 - 1.0e-4 {
+            return None;
+        }
+        // Every pair of amplitudes differing only in this qubit is a
+        // multiple of the qubit's state, so take the largest pair
+        let bit = 1 << (Self::qubit_count() - 1 - index);
+        let rest = (0..N::dim())
+            .filter(|i| i & bit == 0)
+            .max_by(|&a, &b| {
+                let weight = |i: usize| {
+                    self.qubits[i].mag_square()
+                        + self.qubits[i | bit].mag_square()
+                };
+                weight(a).partial_cmp(&weight(b)).unwrap()
+            })
+            .unwrap();
+        let mut qubit = Qubit {
+            inner: Vector2::new(
+                self.qubits[rest],
+                self.qubits[rest | bit],
+            ),
+        };
+        qubit.normalize();
+        Some(qubit.canonical())
+    }
+
+    /// The Bloch vector `(x, y, z)` of a single qubit's reduced state.
+    ///
+    /// This has length 1 for a qubit that isn't entangled with the rest of
+    /// the register, and is shorter the more entangled it is.
+    pub fn qubit_bloch(&self, qubit: usize) -> (Float, Float, Float) {
+        let rho = self.partial_trace(qubit);
+        let coherence = rho[(1, 0)];
+        (
+            2.0 * coherence.re(),
+            2.0 * coherence.im(),
+            rho[(0, 0)].re() - rho[(1, 1)].re(),
+        )
+    }
+
+    /// Whether the state is a product of a state of the qubits in
+    /// `partition` and a state of the remaining qubits.
+    ///
+    /// The amplitudes are arranged into a matrix with a row for each basis
+    /// state of `partition` and a column for each basis state of the rest,
+    /// which is separable exactly when that matrix has rank 1.
+    pub fn is_separable_across(
+        &self,
+        partition: &[usize],
+        epsilon: Float,
+    ) -> bool {
+        let n_qubits = Self::qubit_count();
+        for (i, &q) in partition.iter().enumerate() {
+            assert!(
+                q < n_qubits,
+                "Qubit {} out of range for a {} qubit register",
+                q,
+                n_qubits
+            );
+            assert!(
+                !partition[..i].contains(&q),
+                "Qubit {} is repeated in the partition",
+                q
+            );
+        }
+        let rest: Vec<usize> = (0..n_qubits)
+            .filter(|q| !partition.contains(q))
+            .collect();
+        // Gather the bits of `state` belonging to `qubits`, in order
+        let select = |state: usize, qubits: &[usize]| {
+            qubits.iter().fold(0, |acc, &q| {
+                (acc << 1) | (state >> (n_qubits - 1 - q) & 1)
+            })
+        };
+        let mut mat = vec![
+            vec![Complex::zero(); 1 << rest.len()];
+            1 << partition.len()
+        ];
+        for (i, amp) in self.qubits.iter().enumerate() {
+            mat[select(i, partition)][select(i, &rest)] = *amp;
+        }
+        // A matrix has rank 1 when every row is a multiple of the row
+        // with the largest entry
+        let (r0, c0) = (0..N::dim())
+            .map(|i| (select(i, partition), select(i, &rest)))
+            .max_by(|&(ra, ca), &(rb, cb)| {
+                mat[ra][ca]
+                    .mag_square()
+                    .partial_cmp(&mat[rb][cb].mag_square())
+                    .unwrap()
+            })
+            .unwrap();
+        let pivot = mat[r0][c0];
+        mat.iter().all(|row| {
+            row.iter().zip(&mat[r0]).all(|(&x, &x0)| {
+                let minor = x * pivot - row[c0] * x0;
+                minor.norm() <= epsilon * pivot.norm()
+            })
+        })
+    }
+
+    /// Whether the two registers are equal once a global phase is
+    /// factored out, with each amplitude matching within `epsilon`.
+    pub fn eq_up_to_phase(
+        &self,
+        other: &Self,
+        epsilon: Float,
+    ) -> bool {
+        linalg::eq_up_to_phase(
+            self.qubits.as_slice(),
+            other.qubits.as_slice(),
+            epsilon,
+        )
+    }
+
+    /// The joint state of two independent registers, `|self⟩ ⊗ |other⟩`.
+    ///
+    /// `self` gives the high order qubits, like the first qubit passed to
+    /// [`from_2_qubits`](QuantumRegister::from_2_qubits).
+    pub fn tensor<M: DimName>(
+        &self,
+        other: &QuantumRegister<M>,
+    ) -> QuantumRegister<DimProd<N, M>>
+    where
+        N: DimMul<M>,
+        DimProd<N, M>: DimName,
+        DefaultAllocator:
+            Allocator<Complex, M> + Allocator<Complex, DimProd<N, M>>,
+    {
+        QuantumRegister {
+            qubits: kron_vec(&self.qubits, &other.qubits),
+        }
+    }
+
+    /// The [quantum Fourier transform](https://en.wikipedia.org/wiki/Quantum_Fourier_transform),
+    /// a discrete Fourier transform of the amplitudes:
+    /// `|j⟩ -> 1/√N Σ_k e^{2πi jk/N} |k⟩`
+    pub fn qft(&self) -> Self {
+        self.fourier(1.0)
+    }
+
+    /// The inverse of [`qft`](Self::qft):
+    /// `|k⟩ -> 1/√N Σ_j e^{-2πi jk/N} |j⟩`
+    pub fn iqft(&self) -> Self {
+        self.fourier(-1.0)
+    }
+
+    fn fourier(&self, sign: Float) -> Self {
+        let dim = N::dim();
+        let scale = (dim as Float).sqrt().recip();
+        let qubits = VectorN::<Complex, N>::from_fn(|k, _| {
+            let sum = self.qubits.iter().enumerate().fold(
+                Complex::zero(),
+                |acc, (j, &amp)| {
+                    // Reduce jk mod N first to keep the angle accurate
+                    let turns =
+                        ((j * k) % dim) as Float / dim as Float;
+                    let angle =
+                        sign * 2.0 * crate::consts::PI * turns;
+                    acc + Complex::exp_ix(angle) * amp
+                },
+            );
+            sum * scale
+        });
+        QuantumRegister { qubits }
+    }
+
+    /// Grover's diffusion operator: reflect every amplitude about the
+    /// mean amplitude, `a -> 2·mean - a`
+    pub fn diffuse(&mut self) {
+        let mean = self
+            .qubits
+            .iter()
+            .fold(Complex::zero(), |acc, &amp| acc + amp)
+            / N::dim() as Float;
+        for amp in self.qubits.iter_mut() {
+            *amp = mean * 2.0 - *amp;
+        }
+    }
+
+    /// The inner product `⟨self|other⟩`
+    pub fn inner_product(
+        &self,
+        other: &QuantumRegister<N>,
+    ) -> Complex {
+        self.qubits
+            .iter()
+            .zip(other.qubits.iter())
+            .fold(Complex::zero(), |acc, (a, b)| acc + a.conj() * *b)
+    }
+
+    /// `|⟨self|other⟩|²`, 1 for the same state and 0 for orthogonal ones
+    pub fn fidelity(&self, other: &QuantumRegister<N>) -> Float {
+        self.inner_product(other).mag_square()
+    }
+
+    /// The probability of measuring each basis state, in order
+    pub fn probabilities(&self) -> Vec<Float> {
+        self.qubits.iter().map(|amp| amp.mag_square()).collect()
+    }
+
+    /// The amplitude of the basis state labelled `label`, such as `"01"`,
+    /// with qubit 0 first. This is `None` unless `label` has one `0` or
+    /// `1` per qubit.
+    pub fn amplitude_of(&self, label: &str) -> Option<Complex> {
+        if label.len() != self.num_qubits() {
+            return None;
+        }
+        let state = label.chars().try_fold(0, |acc, c| match c {
+            '0' => Some(acc << 1),
+            '1' => Some((acc << 1) | 1),
+            _ => None,
+        })?;
+        Some(self.qubits[state])
+    }
+
+    /// The probability of measuring basis state `state`
+    pub fn probability(&self, state: usize) -> Float {
+        self.qubits[state].mag_square()
+    }
+
+    /// The most probable measurement outcome and its probability. Ties
+    /// go to the lowest basis state.
+    pub fn most_likely(&self) -> (ClassicalRegister, Float) {
+        let (state, prob) = self
+            .probabilities()
+            .into_iter()
+            .enumerate()
+            .fold((0, 0.0), |best, (state, p)| {
+                if p > best.1 {
+                    (state, p)
+                } else {
+                    best
+                }
+            });
+        ((state as u64).into(), prob)
+    }
+
+    /// The [Shannon entropy](https://en.wikipedia.org/wiki/Entropy_(information_theory)),
+    /// in bits, of the measurement outcome: 0 for a basis state, and
+    /// `log2(N)` for a uniform superposition.
+    pub fn shannon_entropy(&self) -> Float {
+        self.probabilities()
+            .iter()
+            .filter(|&&p| p > 0.0)
+            .map(|&p| -p * p.log2())
+            .sum()
+    }
+
+    /// The chance that two independent measurements give the same
+    /// outcome: 1 for a basis state, and `1/N` for a uniform
+    /// superposition.
+    pub fn collision_probability(&self) -> Float {
+        self.probabilities().iter().map(|p| p * p).sum()
+    }
+
+    /// The basis states with a probability of at least `threshold`, and
+    /// their amplitudes, in order
+    pub fn significant_amplitudes(
+        &self,
+        threshold: Float,
+    ) -> Vec<(usize, Complex)> {
+        self.qubits
+            .iter()
+            .enumerate()
+            .filter(|(_, amp)| amp.mag_square() >= threshold)
+            .map(|(state, &amp)| (state, amp))
+            .collect()
+    }
+
+    /// The basis states with an amplitude bigger than `epsilon`
+    pub fn support(&self, epsilon: Float) -> Vec<usize> {
+        self.qubits
+            .iter()
+            .enumerate()
+            .filter(|(_, amp)| amp.norm() > epsilon)
+            .map(|(state, _)| state)
+            .collect()
+    }
+
+    /// The number of basis states, `N`
+    pub fn num_states(&self) -> usize {
+        self.qubits.len()
+    }
+
+    /// The number of qubits, `log2(N)`
+    pub fn num_qubits(&self) -> usize {
+        self.num_states().trailing_zeros() as usize
+    }
+
+    fn qubit_count() -> usize {
+        N::dim().trailing_zeros() as usize
+    }
+}
+
+/// Pick a basis state of `amps` for the random float `target` in
+/// `[0, 1)`.
+///
+/// Each state with nonzero probability owns a slice of [0, 1) as long
+/// as its probability, in order, and we pick the one holding target.
+/// States with zero probability are never picked. If rounding leaves
+/// the total just short of target, the last state with nonzero
+/// probability gets the leftover.
+pub(crate) fn collapse_amplitudes(
+    amps: &[Complex],
+    target: Float,
+) -> ClassicalRegister {
+    // The largest `Float` below 1
+    let below_one = 1.0 - Float::EPSILON / 2.0;
+    let target = target.clamp(0.0, below_one);
+    let mut cumulative = 0.0;
+    let mut last_nonzero = None;
+    for (bits, amp) in amps.iter().enumerate() {
+        let prob = amp.mag_square();
+        if prob == 0.0 {
+            continue;
+        }
+        cumulative += prob;
+        last_nonzero = Some(bits as u64);
+        if target < cumulative {
+            break;
+        }
+    }
+    ClassicalRegister {
+        bits: last_nonzero.expect(
+            "Can't collapse a register of all zero amplitudes",
+        ),
+    }
+}
+
+/// Dirac notation, like `0.707|00⟩ + 0.707|11⟩`. Amplitudes are
+/// printed to 3 decimal places unless a precision is given, and
+/// negligible ones are left out.
+impl<N: DimName> fmt::Display for QuantumRegister<N>
+where
+    DefaultAllocator: Allocator<Complex, N>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let precision = f.precision().unwrap_or(3);
+        let width = self.num_qubits();
+        let terms = self.significant_amplitudes(1.0e-6);
+        for (i, (state, amp)) in terms.iter().enumerate() {
+            if i != 0 {
+                write!(f, " + ")?;
+            }
+            if amp.re() != 0.0 && amp.im() != 0.0 {
+                write!(f, "({:.*})", precision, amp)?;
+            } else {
+                write!(f, "{:.*}", precision, amp)?;
+            }
+            write!(f, "|{:0width$b}⟩", state, width = width)?;
+        }
+        if terms.is_empty() {
+            write!(f, "0")?;
+        }
+        Ok(())
+    }
+}
+
+/// Serialized as its amplitude vector
+#[cfg(feature = "serde")]
+impl<N: DimName> serde::Serialize for QuantumRegister<N>
+where
+    DefaultAllocator: Allocator<Complex, N>,
+{
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.qubits.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, N: DimName> serde::Deserialize<'de> for QuantumRegister<N>
+where
+    DefaultAllocator: Allocator<Complex, N>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let amps: Vec<Complex> =
+            serde::Deserialize::deserialize(deserializer)?;
+        if amps.len() != N::dim() {
+            return Err(D::Error::invalid_length(
+                amps.len(),
+                &format!("{} amplitudes", N::dim()).as_str(),
+            ));
+        }
+        let qubits = VectorN::<Complex, N>::from_iterator(amps);
+        Self::try_from_vector(qubits).map_err(D::Error::custom)
+    }
+}
+
+/// The basis state `|state⟩`, as [`basis`](QuantumRegister::basis).
+///
+/// Panics if `state` is out of range.
+impl<N: DimName> From<u64> for QuantumRegister<N>
+where
+    DefaultAllocator: Allocator<Complex, N>,
+{
+    fn from(state: u64) -> Self {
+        Self::basis(state as usize)
+    }
+}
+
+impl From<Qubit> for QuantumRegister<U2> {
+    fn from(q: Qubit) -> QuantumRegister<U2> {
+        QuantumRegister { qubits: q.inner }
+    }
+}
+
+/// The qubit a one qubit register holds, or
+/// [`NotNormalized`](RegisterError::NotNormalized) if its amplitudes
+/// have drifted too far from unit length.
+impl TryFrom<QuantumRegister<U2>> for Qubit {
+    type Error = RegisterError;
+    fn try_from(
+        reg: QuantumRegister<U2>,
+    ) -> Result<Qubit, RegisterError> {
+        if QuantumRegister::is_valid(&reg.qubits) {
+            Ok(Qubit { inner: reg.qubits })
+        } else {
+            Err(RegisterError::NotNormalized)
+        }
+    }
+}
+
+impl QuantumRegister<U4> {
+    pub fn from_2_qubits(qa: Qubit, qb: Qubit) -> Self {
+        let (qa_0, qa_1) = (qa.inner.index(0), qa.inner.index(1));
+        let (qb_0, qb_1) = (qb.inner.index(0), qb.inner.index(1));
+        let ket_00 = *qa_0 * *qb_0;
+        let ket_01 = *qa_0 * *qb_1;
+        let ket_10 = *qa_1 * *qb_0;
+        let ket_11 = *qa_1 * *qb_1;
+        Self {
+            qubits: Vector4::new(ket_00, ket_01, ket_10, ket_11),
+        }
+    }
+
+    /// The [Bell state](https://en.wikipedia.org/wiki/Bell_state)
+    /// `|Φ+⟩ = (|00⟩ + |11⟩)/√2`
+    pub fn bell_phi_plus() -> Self {
+        Self::bell(0b00, 0b11, 1.0)
+    }
+
+    /// The Bell state `|Φ-⟩ = (|00⟩ - |11⟩)/√2`
+    pub fn bell_phi_minus() -> Self {
+        Self::bell(0b00, 0b11, -1.0)
+    }
+
+    /// The Bell state `|Ψ+⟩ = (|01⟩ + |10⟩)/√2`
+    pub fn bell_psi_plus() -> Self {
+        Self::bell(0b01, 0b10, 1.0)
+    }
+
+    /// The Bell state `|Ψ-⟩ = (|01⟩ - |10⟩)/√2`
+    pub fn bell_psi_minus() -> Self {
+        Self::bell(0b01, 0b10, -1.0)
+    }
+
+    /// `(|first⟩ + sign |second⟩)/√2`
+    fn bell(first: usize, second: usize, sign: Float) -> Self {
+        let amp = crate::consts::FRAC_1_SQRT_2;
+        let mut qubits = Vector4::zeros();
+        qubits[first] = amp.into();
+        qubits[second] = (sign * amp).into();
+        QuantumRegister { qubits }
+    }
+
+    /// [`BinaryGate::apply`], without consuming the register
+    pub fn apply_in_place(&mut self, gate: &BinaryGate) {
+        self.qubits = gate.mat * self.qubits;
+    }
+
+    /// The expectation value `Re(⟨ψ|O|ψ⟩)` of measuring `observable`.
+    /// This is only meaningful for a hermitian observable, such as a
+    /// tensor product of Pauli gates.
+    pub fn expectation(&self, observable: &BinaryGate) -> Float {
+        let applied = observable.mat * self.qubits;
+        self.qubits
+            .iter()
+            .zip(applied.iter())
+            .fold(Complex::zero(), |acc, (a, b)| acc + a.conj() * *b)
+            .re()
+    }
+}
+impl QuantumRegister<U4> {
+    /// A circuit of H, S and CNOT gates that prepares this state from
+    /// `|00⟩`, up to a global phase, or `None` if it isn't a
+    /// [stabilizer state](https://en.wikipedia.org/wiki/Stabilizer_state).
+    pub fn preparation_circuit(&self) -> Option<Circuit> {
+        let steps = [
+            Operation::Unary {
+                gate: h(),
+                target: 0,
+            },
+            Operation::Unary {
+                gate: h(),
+                target: 1,
+            },
+            Operation::Unary {
+                gate: s(),
+                target: 0,
+            },
+            Operation::Unary {
+                gate: s(),
+                target: 1,
+            },
+            Operation::Binary {
+                gate: cnot(),
+                first: 0,
+                second: 1,
+            },
+            Operation::Binary {
+                gate: cnot(),
+                first: 1,
+                second: 0,
+            },
+        ];
+        // There are only 60 two qubit stabilizer states, so search all of
+        // them breadth first, giving the shortest circuit.
+        let start = (Self::from_classical(0.into()), Circuit::new(2));
+        let mut seen = vec![start.0.clone()];
+        let mut queue = std::collections::VecDeque::from(vec![start]);
+        while let Some((state, circuit)) = queue.pop_front() {
+            if state.eq_up_to_phase(self, 1e-4) {
+                return Some(circuit);
+            }
+            for op in &steps {
+                let next = Circuit::new(2)
+                    .push(op.clone())
+                    .run(state.clone());
+                if !seen.iter().any(|s| s.eq_up_to_phase(&next, 1e-4))
+                {
+                    seen.push(next.clone());
+                    queue.push_back((
+                        next,
+                        circuit.clone().push(op.clone()),
+                    ));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl QuantumRegister<U8> {
+    /// The [W state](https://en.wikipedia.org/wiki/W_state)
+    /// `(|001⟩ + |010⟩ + |100⟩)/√3`
+    pub fn w_state() -> Self {
+        let amp = Complex::from_re((3 as Float).sqrt().recip());
+        let mut qubits = VectorN::<Complex, U8>::zeros();
+        qubits[0b001] = amp;
+        qubits[0b010] = amp;
+        qubits[0b100] = amp;
+        debug_assert!(Self::is_valid(&qubits));
+        QuantumRegister { qubits }
+    }
+
+    /// The [three-tangle](https://en.wikipedia.org/wiki/Coffman%E2%80%93Kundu%E2%80%93Wootters_inequality)
+    /// `4|Det(a)|`, where `Det` is Cayley's hyperdeterminant of the
+    /// amplitudes. This is 1 for GHZ and 0 for W or any state with a
+    /// qubit that isn't entangled with the others.
+    pub fn three_tangle(&self) -> Float {
+        let a = |i: usize, j: usize, k: usize| {
+            self.qubits[i << 2 | j << 1 | k]
+        };
+        let d1 = a(0, 0, 0) * a(0, 0, 0) * a(1, 1, 1) * a(1, 1, 1)
+            + a(0, 0, 1) * a(0, 0, 1) * a(1, 1, 0) * a(1, 1, 0)
+            + a(0, 1, 0) * a(0, 1, 0) * a(1, 0, 1) * a(1, 0, 1)
+            + a(1, 0, 0) * a(1, 0, 0) * a(0, 1, 1) * a(0, 1, 1);
+        let d2 = a(0, 0, 0) * a(1, 1, 1) * a(0, 1, 1) * a(1, 0, 0)
+            + a(0, 0, 0) * a(1, 1, 1) * a(1, 0, 1) * a(0, 1, 0)
+            + a(0, 0, 0) * a(1, 1, 1) * a(1, 1, 0) * a(0, 0, 1)
+            + a(0, 1, 1) * a(1, 0, 0) * a(1, 0, 1) * a(0, 1, 0)
+            + a(0, 1, 1) * a(1, 0, 0) * a(1, 1, 0) * a(0, 0, 1)
+            + a(1, 0, 1) * a(0, 1, 0) * a(1, 1, 0) * a(0, 0, 1);
+        let d3 = a(0, 0, 0) * a(1, 1, 0) * a(1, 0, 1) * a(0, 1, 1)
+            + a(1, 1, 1) * a(0, 0, 1) * a(0, 1, 0) * a(1, 0, 0);
+        let det = d1 - d2 * 2.0 + d3 * 4.0;
+        4.0 * det.norm()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use typenum::consts::U256;
+
+    #[test]
+    fn from_classical() {
+        for i in 0..255 {
+            let reg =
+                QuantumRegister::<U256>::from_classical(i.into());
+            assert_eq!(reg.collapse(), i.into())
+        }
+    }
+
+    #[test]
+    fn try_from_classical() {
+        let reg =
+            QuantumRegister::<U4>::try_from_classical(0b11.into());
+        assert_eq!(reg.unwrap().collapse(), 0b11.into());
+        // A 3 bit value can't be loaded into a 2 qubit register
+        assert_eq!(
+            QuantumRegister::<U4>::try_from_classical(0b100.into()),
+            Err(RegisterError::OutOfRange {
+                state: 0b100,
+                states: 4
+            })
+        );
+    }
+
+    #[test]
+    fn from_fn() {
+        use crate::consts::FRAC_1_SQRT_2;
+        let bell = QuantumRegister::<U4>::from_fn(|i| {
+            if i == 0 || i == 3 {
+                FRAC_1_SQRT_2.into()
+            } else {
+                Complex::zero()
+            }
+        });
+        assert_relative_eq!(
+            bell.into_vector(),
+            bell_state().into_vector(),
+            epsilon = 1e-6
+        );
+        // Amplitudes are normalized for you
+        let uniform =
+            QuantumRegister::<U8>::from_fn(|_| Complex::from_re(3.0));
+        assert_relative_eq!(
+            uniform.into_vector(),
+            QuantumRegister::<U8>::uniform().into_vector(),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "A register needs a nonzero amplitude")]
+    fn from_fn_all_zero() {
+        QuantumRegister::<U4>::from_fn(|_| Complex::zero());
+    }
+
+    #[test]
+    fn map_amplitudes() {
+        // Tag the odd basis states with a phase of -1
+        let mut reg = QuantumRegister::<U8>::uniform();
+        reg.map_amplitudes(
+            |i, amp| if i % 2 == 1 { -amp } else { amp },
+        );
+        assert!(reg.is_valid_state());
+        for (i, amp) in reg.amplitudes().iter().enumerate() {
+            let sign = if i % 2 == 1 { -1.0 } else { 1.0 };
+            assert_relative_eq!(
+                amp.re(),
+                sign / (8.0 as Float).sqrt(),
+                epsilon = 1e-6
+            );
+        }
+        // Scaling breaks the norm until it's normalized again
+        reg.map_amplitudes(|_, amp| amp * 2.0);
+        assert!(!reg.is_valid_state());
+        reg.normalize();
+        assert!(reg.is_valid_state());
+    }
+
+    #[test]
+    fn superposition() {
+        assert_relative_eq!(
+            QuantumRegister::<U4>::superposition(&[0b00, 0b11])
+                .into_vector(),
+            bell_state().into_vector(),
+            epsilon = 1e-6
+        );
+        let reg = QuantumRegister::<U4>::superposition(&[
+            0b01, 0b00, 0b11, 0b01,
+        ]);
+        let probs = reg.probabilities();
+        for (&p, &expected) in
+            probs.iter().zip(&[1.0 / 3.0, 1.0 / 3.0, 0.0, 1.0 / 3.0])
+        {
+            assert_relative_eq!(p, expected, epsilon = 1e-6);
+        }
+        assert_eq!(
+            QuantumRegister::<U8>::superposition(&[5]),
+            QuantumRegister::basis(5)
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Basis state 4 out of range for a 4 state register"
+    )]
+    fn superposition_out_of_range() {
+        QuantumRegister::<U4>::superposition(&[0, 4]);
+    }
+
+    #[test]
+    fn from_array() {
+        let (zero, amp) = (
+            Complex::zero(),
+            Complex::from_re(crate::consts::FRAC_1_SQRT_2),
+        );
+        let bell =
+            QuantumRegister::<U4>::from_array([amp, zero, zero, amp])
+                .unwrap();
+        assert_eq!(bell, bell_state());
+        assert_eq!(bell.amplitudes(), &[amp, zero, zero, amp]);
+        assert_eq!(
+            QuantumRegister::<U4>::from_array([amp; 4]),
+            Err(RegisterError::NotNormalized)
+        );
+        assert_eq!(
+            QuantumRegister::<U4>::from_array([amp, amp]),
+            Err(RegisterError::WrongLength {
+                expected: 4,
+                got: 2
+            })
+        );
+    }
+
+    #[test]
+    fn amplitude_encode() {
+        let reg = QuantumRegister::<U4>::amplitude_encode(&[
+            3.0, 4.0, 0.0, 0.0,
+        ])
+        .unwrap();
+        let probs: Vec<Float> =
+            reg.qubits.iter().map(|x| x.mag_square()).collect();
+        for (p, expected) in probs.iter().zip(&[0.36, 0.64, 0.0, 0.0])
+        {
+            assert_relative_eq!(p, expected);
+        }
+        assert_eq!(
+            QuantumRegister::<U4>::amplitude_encode(&[0.0; 4]),
+            Err(RegisterError::ZeroVector)
+        );
+        assert_eq!(
+            QuantumRegister::<U4>::amplitude_encode(&[1.0; 3]),
+            Err(RegisterError::WrongLength {
+                expected: 4,
+                got: 3
+            })
+        );
+    }
+
+    #[test]
+    fn apply_unary() {
+        use crate::gates::unitary::gates::{h, not};
+        let reg = QuantumRegister::<U4>::from_classical(0b00.into());
+        let flipped = reg.apply_unary(&not(), 0);
+        assert_eq!(
+            flipped,
+            QuantumRegister::from_classical(0b10.into())
+        );
+        let flipped = reg.apply_unary(&not(), 1);
+        assert_eq!(
+            flipped,
+            QuantumRegister::from_classical(0b01.into())
+        );
+
+        let (reg, description) = reg.apply_unary_traced(&h(), 0);
+        assert_relative_eq!(
+            reg.qubits,
+            QuantumRegister::from_2_qubits(
+                Qubit::plus(),
+                Qubit::zero()
+            )
+            .qubits
+        );
+        assert!(description.contains("qubit 0"), "{}", description);
+        assert_eq!(description, "H on qubit 0");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Target qubit 2 out of range for a 2 qubit register"
+    )]
+    fn apply_unary_out_of_range() {
+        use crate::gates::unitary::gates::h;
+        QuantumRegister::<U4>::from_classical(0.into())
+            .apply_unary(&h(), 2);
+    }
+
+    #[test]
+    fn qubit_bloch_bell_is_mixed() {
+        let bell = bell_state();
+        for qubit in 0..2 {
+            let (x, y, z) = bell.qubit_bloch(qubit);
+            assert_relative_eq!(x, 0.0);
+            assert_relative_eq!(y, 0.0);
+            assert_relative_eq!(z, 0.0);
+        }
+    }
+
+    #[test]
+    fn ghz() {
+        let ghz = QuantumRegister::<U8>::ghz();
+        assert!(QuantumRegister::is_valid(&ghz.qubits));
+        for _ in 0..100 {
+            match ghz.collapse().bits {
+                0b000 | 0b111 => {}
+                _ => panic!(
+                    "GHZ state can only collapse to 000 or 111"
+                ),
+            }
+        }
+        for qubit in 0..3 {
+            assert_relative_eq!(
+                ghz.partial_trace(qubit),
+                Matrix2::identity() * Complex::from_re(0.5),
+                epsilon = 1e-6
+            );
+        }
+        assert_eq!(QuantumRegister::<U4>::ghz(), bell_state());
+    }
+
+    #[test]
+    fn qubit_round_trip() {
+        for q in &[
+            Qubit::plus(),
+            Qubit::one(),
+            Qubit::from_theta_phi(1.2, -0.4),
+        ] {
+            let reg = QuantumRegister::<U2>::from(q.clone());
+            assert_eq!(Qubit::try_from(reg), Ok(q.clone()));
+        }
+        let evolved = QuantumRegister::<U2>::from(Qubit::zero())
+            .apply_unary(&h(), 0);
+        assert_relative_eq!(
+            Qubit::try_from(evolved).unwrap(),
+            Qubit::plus()
+        );
+        let drifted = QuantumRegister::<U2>::from_vector_unchecked(
+            nalgebra::Vector2::new(Complex::one(), Complex::one()),
+        );
+        assert_eq!(
+            Qubit::try_from(drifted),
+            Err(RegisterError::NotNormalized)
+        );
+    }
+
+    #[test]
+    fn inner_product_and_fidelity() {
+        use crate::gates::unitary::gates::pauli::{x, z};
+        let bell = bell_state();
+        assert_relative_eq!(
+            bell.fidelity(&bell),
+            1.0,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            bell.inner_product(&bell),
+            Complex::one(),
+            epsilon = 1e-6
+        );
+        // The other Bell states
+        let phi_minus = bell.apply_unary(&z(), 0);
+        let psi_plus = bell.apply_unary(&x(), 0);
+        let psi_minus = phi_minus.apply_unary(&x(), 0);
+        let bells = [bell, phi_minus, psi_plus, psi_minus];
+        for (i, a) in bells.iter().enumerate() {
+            for (j, b) in bells.iter().enumerate() {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert_relative_eq!(
+                    a.fidelity(b),
+                    expected,
+                    epsilon = 1e-6
+                );
+            }
+        }
+        let zero = QuantumRegister::<U4>::basis(0b00);
+        assert_eq!(zero.fidelity(&QuantumRegister::basis(0b01)), 0.0);
+        assert_relative_eq!(
+            zero.fidelity(&QuantumRegister::<U4>::uniform()),
+            0.25,
+            epsilon = 1e-6
+        );
+        // ⟨a|b⟩ is the conjugate of ⟨b|a⟩
+        let a = QuantumRegister::<U2>::from(Qubit::from_theta_phi(
+            0.5, 1.0,
+        ));
+        let b = QuantumRegister::<U2>::from(Qubit::from_theta_phi(
+            2.0, -0.3,
+        ));
+        assert_relative_eq!(
+            a.inner_product(&b),
+            b.inner_product(&a).conj(),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn extract_qubit() {
+        let q = Qubit::from_theta_phi(0.8, 2.5);
+        let reg = QuantumRegister::<U4>::from_2_qubits(
+            Qubit::minus(),
+            q.clone(),
+        )
+        .tensor(&QuantumRegister::<U2>::from(Qubit::one()));
+        assert_relative_eq!(
+            reg.extract_qubit(1).unwrap(),
+            q.canonical(),
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            reg.extract_qubit(0).unwrap(),
+            Qubit::minus(),
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            reg.extract_qubit(2).unwrap(),
+            Qubit::one()
+        );
+        assert_eq!(bell_state().extract_qubit(0), None);
+        assert_eq!(
+            QuantumRegister::<U8>::w_state().extract_qubit(2),
+            None
+        );
+    }
+
+    #[test]
+    fn num_states_and_qubits() {
+        let reg = QuantumRegister::<U4>::basis(0);
+        assert_eq!(reg.num_states(), 4);
+        assert_eq!(reg.num_qubits(), 2);
+        let reg = QuantumRegister::<U2>::from(Qubit::plus());
+        assert_eq!((reg.num_states(), reg.num_qubits()), (2, 1));
+        let reg = QuantumRegister::<U8>::w_state();
+        assert_eq!((reg.num_states(), reg.num_qubits()), (8, 3));
+    }
+
+    #[test]
+    fn uniform() {
+        let uniform = QuantumRegister::<U4>::uniform();
+        assert!(QuantumRegister::is_valid(&uniform.qubits));
+        assert_eq!(uniform.probabilities(), vec![0.25; 4]);
+        assert_relative_eq!(
+            uniform.into_vector(),
+            QuantumRegister::<U4>::from_2_qubits(
+                Qubit::plus(),
+                Qubit::plus()
+            )
+            .into_vector(),
+            epsilon = 1e-6
+        );
+        let uniform = QuantumRegister::<U8>::uniform();
+        assert!(QuantumRegister::is_valid(&uniform.qubits));
+        assert_relative_eq!(
+            uniform.into_vector(),
+            QuantumRegister::<U8>::basis(0).qft().into_vector(),
+            epsilon = 1e-6
+        );
+    }
+
+    /// Whether qubits 1 and 2 are entangled once qubit 0 is traced out,
+    /// by the Peres-Horodecki criterion: for two qubits, the partial
+    /// transpose has a negative eigenvalue exactly when they're
+    /// entangled, and there can only be one.
+    fn last_pair_entangled(reg: &QuantumRegister<U8>) -> bool {
+        let rho = nalgebra::Matrix4::from_fn(|j, k| {
+            (0..2).fold(Complex::zero(), |acc, a| {
+                acc + reg.qubits[a * 4 + j]
+                    * reg.qubits[a * 4 + k].conj()
+            })
+        });
+        let transposed = nalgebra::Matrix4::from_fn(|j, k| {
+            rho[((j & 2) | (k & 1), (k & 2) | (j & 1))]
+        });
+        crate::linalg::determinant(&transposed).re() < -1e-6
+    }
+
+    #[test]
+    fn w_state() {
+        let w = QuantumRegister::<U8>::w_state();
+        assert!(QuantumRegister::is_valid(&w.qubits));
+        assert_eq!(w.support(1e-6), vec![0b001, 0b010, 0b100]);
+        for qubit in 0..3 {
+            // Mixed, but not maximally like GHZ
+            let (x, y, z) = w.qubit_bloch(qubit);
+            let length = (x * x + y * y + z * z).sqrt();
+            assert_relative_eq!(length, 1.0 / 3.0, epsilon = 1e-6);
+            assert_relative_eq!(
+                w.partial_trace(qubit)[(0, 0)],
+                Complex::from_re(2.0 / 3.0),
+                epsilon = 1e-6
             );
-        qubits[cr.bits as usize] = Complex::one();
+        }
+        assert!(last_pair_entangled(&w));
+        assert!(!last_pair_entangled(&QuantumRegister::<U8>::ghz()));
+    }
 
-        debug_assert!(Self::is_valid(&qubits));
-        QuantumRegister { qubits }
+    #[test]
+    fn three_tangle() {
+        let ghz = QuantumRegister::<U8>::ghz();
+        assert_relative_eq!(ghz.three_tangle(), 1.0, epsilon = 1e-6);
+        let w = QuantumRegister::<U8>::w_state();
+        assert_relative_eq!(w.three_tangle(), 0.0, epsilon = 1e-6);
+        let basis = QuantumRegister::<U8>::basis(0b101);
+        assert_relative_eq!(basis.three_tangle(), 0.0);
+        // Local gates can't change it
+        let rotated = ghz.apply_unary(&h(), 1).apply_unary(
+            &UnaryGate::from_parameters((0.3, 1.2, 0.5, 0.0)),
+            2,
+        );
+        assert_relative_eq!(
+            rotated.three_tangle(),
+            1.0,
+            epsilon = 1e-5
+        );
     }
 
-    #[must_use]
-    fn is_valid(vector: &VectorN<Complex, N>) -> bool {
-        let mut acc = 0.0;
-        for i in vector.iter() {
-            acc += i.mag_square()
+    #[test]
+    fn seeded_collapse_is_reproducible() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        let reg =
+            QuantumRegister::<U8>::w_state().apply_unary(&h(), 0);
+        let shots = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let bits: Vec<_> = (0..100)
+                .map(|_| reg.collapse_with_rng(&mut rng).bits)
+                .collect();
+            let samples: Vec<_> = (0..100)
+                .map(|_| Qubit::plus().sample_with_rng(&mut rng))
+                .collect();
+            (bits, samples)
+        };
+        assert_eq!(shots(7), shots(7));
+        assert_ne!(shots(7), shots(8));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Basis state 5 out of range for a 4 state register"
+    )]
+    fn from_classical_out_of_range() {
+        QuantumRegister::<U4>::from_classical(5.into());
+    }
+
+    #[test]
+    fn marginal_probability() {
+        let bell = bell_state();
+        for qubit in 0..2 {
+            assert_relative_eq!(
+                bell.marginal_probability(qubit),
+                0.5,
+                epsilon = 1e-6
+            );
+        }
+        // Qubit 0 is the high bit, so in |01⟩ it's qubit 1 that's set
+        let product = QuantumRegister::<U4>::basis(0b01);
+        assert_eq!(product.marginal_probability(0), 0.0);
+        assert_eq!(product.marginal_probability(1), 1.0);
+        let product = QuantumRegister::<U4>::basis(0b10);
+        assert_eq!(product.marginal_probability(0), 1.0);
+        assert_eq!(product.marginal_probability(1), 0.0);
+        let w = QuantumRegister::<U8>::w_state();
+        for qubit in 0..3 {
+            assert_relative_eq!(
+                w.marginal_probability(qubit),
+                1.0 / 3.0,
+                epsilon = 1e-6
+            );
         }
-        (acc - 1.0).abs() <= 1.0e-6
     }
 
-    // Target should be a random float. Used for edge case tests.
-    fn collapse_with_target(&self, target: f32) -> ClassicalRegister {
-        let target = target % 1.0;
-        let mut current = 0.0;
-        // Handle for floating point problems
-        let mut reserve: Option<u8> = None;
-        for (bits, im_prob) in self.qubits.iter().enumerate() {
-            let prob = im_prob.mag_square();
-            current += prob;
-            if current > target {
-                return ClassicalRegister {
-                    bits: bits
-                        .try_into()
-                        .expect("This should never be more than 255"),
-                };
-            // Set the reserve to whatever
-            } else if prob != 0.0 {
-                reserve = Some(bits.try_into().unwrap());
+    #[test]
+    fn measure() {
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let mut rng = SmallRng::seed_from_u64(4);
+        for _ in 0..20 {
+            let mut reg = QuantumRegister::<U8>::uniform();
+            let first = reg.measure_with_rng(&mut rng);
+            assert_eq!(
+                reg,
+                QuantumRegister::basis(first.bits as usize)
+            );
+            for _ in 0..10 {
+                assert_eq!(reg.measure(), first);
             }
         }
-        // If we didn't get anything, use the reserve which must be something
-        // because some item must have non zero probability
-        ClassicalRegister {
-            bits: reserve.unwrap(),
+    }
+
+    #[test]
+    fn measure_qubit_bell_correlation() {
+        let mut ones = 0;
+        for _ in 0..1000 {
+            let mut bell = bell_state();
+            let first = bell.measure_qubit(0);
+            assert!(QuantumRegister::is_valid(&bell.qubits));
+            let expected = if first { 0b11 } else { 0b00 };
+            for _ in 0..10 {
+                assert_eq!(bell.collapse().bits, expected);
+            }
+            ones += first as u32;
         }
+        assert!(425 < ones && ones < 575, "{} ones", ones);
     }
 
-    pub fn collapse(&self) -> ClassicalRegister {
-        self.collapse_with_target(rand::random::<f32>())
+    #[test]
+    fn measure_qubit_keeps_rest() {
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let mut rng = SmallRng::seed_from_u64(1);
+        let plus = QuantumRegister::from_2_qubits(
+            Qubit::one(),
+            Qubit::plus(),
+        );
+        let mut reg = plus.clone();
+        assert!(reg.measure_qubit_with_rng(0, &mut rng));
+        assert_relative_eq!(reg.qubits, plus.qubits);
+        let mut reg =
+            QuantumRegister::<U8>::ghz().apply_unary(&h(), 2);
+        let first = reg.measure_qubit_with_rng(1, &mut rng);
+        assert_eq!(reg.support(1e-6).len(), 2);
+        assert_eq!(reg.measure_qubit_with_rng(0, &mut rng), first);
     }
 
-    pub fn from_vector(qubits: VectorN<Complex, N>) -> Self {
-        Self { qubits }
+    #[test]
+    fn is_valid_state() {
+        let bell = bell_state();
+        assert!(bell.is_valid_state());
+        assert_relative_eq!(
+            bell.total_probability(),
+            1.0,
+            epsilon = 1e-6
+        );
+        let mut reg = QuantumRegister::<U8>::w_state();
+        for target in 0..3 {
+            reg = reg
+                .apply_unary(&h(), target)
+                .apply_unary(&s(), target);
+            assert!(reg.is_valid_state());
+        }
+        let doubled = QuantumRegister::from_vector_unchecked(
+            bell.into_vector() * Complex::from_re(2.0),
+        );
+        assert!(!doubled.is_valid_state());
+        assert_relative_eq!(
+            doubled.total_probability(),
+            4.0,
+            epsilon = 1e-6
+        );
     }
 
-    pub fn into_vector(self) -> VectorN<Complex, N> {
-        self.qubits
+    #[test]
+    fn reverse_qubits() {
+        let amps = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+        let reg =
+            QuantumRegister::<U8>::amplitude_encode(&amps).unwrap();
+        let mut reversed = reg.clone();
+        reversed.reverse_qubits();
+        for state in 0..8 {
+            let mirrored =
+                ((state & 1) << 2) | (state & 0b010) | (state >> 2);
+            assert_eq!(reversed.qubits[mirrored], reg.qubits[state]);
+        }
+        reversed.reverse_qubits();
+        assert_eq!(reversed, reg);
+
+        let mut two = QuantumRegister::<U4>::amplitude_encode(&[
+            1.0, 2.0, 3.0, 4.0,
+        ])
+        .unwrap();
+        let before = two.clone();
+        two.reverse_qubits();
+        assert_eq!(two.qubits[0b01], before.qubits[0b10]);
+        assert_eq!(two.qubits[0b10], before.qubits[0b01]);
+        assert_eq!(two.qubits[0b00], before.qubits[0b00]);
+        assert_eq!(two.qubits[0b11], before.qubits[0b11]);
     }
-}
 
-impl From<Qubit> for QuantumRegister<U2> {
-    fn from(q: Qubit) -> QuantumRegister<U2> {
-        QuantumRegister { qubits: q.inner }
+    #[test]
+    fn apply_global_phase() {
+        let reg =
+            QuantumRegister::<U8>::w_state().apply_unary(&h(), 1);
+        let mut rotated = reg.clone();
+        rotated.apply_global_phase(1.7);
+        assert_ne!(rotated, reg);
+        assert!(rotated.eq_up_to_phase(&reg, 1e-6));
+        for (p, q) in
+            rotated.probabilities().iter().zip(reg.probabilities())
+        {
+            assert_relative_eq!(*p, q, epsilon = 1e-6);
+        }
+        rotated.apply_global_phase(-1.7);
+        assert_relative_eq!(
+            rotated.into_vector(),
+            reg.into_vector(),
+            epsilon = 1e-6
+        );
     }
-}
 
-impl QuantumRegister<U4> {
-    pub fn from_2_qubits(qa: Qubit, qb: Qubit) -> Self {
-        let (qa_0, qa_1) = (qa.inner.index(0), qa.inner.index(1));
-        let (qb_0, qb_1) = (qb.inner.index(0), qb.inner.index(1));
-        let ket_00 = *qa_0 * *qb_0;
-        let ket_01 = *qa_0 * *qb_1;
-        let ket_10 = *qa_1 * *qb_0;
-        let ket_11 = *qa_1 * *qb_1;
-        Self {
-            qubits: Vector4::new(ket_00, ket_01, ket_10, ket_11),
+    #[test]
+    fn bell_basis() {
+        assert_eq!(QuantumRegister::bell_phi_plus(), bell_state());
+        let bells = [
+            QuantumRegister::bell_phi_plus(),
+            QuantumRegister::bell_phi_minus(),
+            QuantumRegister::bell_psi_plus(),
+            QuantumRegister::bell_psi_minus(),
+        ];
+        for (i, a) in bells.iter().enumerate() {
+            assert!(QuantumRegister::is_valid(&a.qubits));
+            for (j, b) in bells.iter().enumerate() {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert_relative_eq!(
+                    a.inner_product(b),
+                    Complex::from_re(expected),
+                    epsilon = 1e-6
+                );
+            }
         }
+        // The singlet picks up a sign when its qubits are swapped
+        let singlet = QuantumRegister::bell_psi_minus();
+        let swapped = crate::gates::binary::gates::swap()
+            .apply(singlet.clone());
+        assert_relative_eq!(
+            swapped.into_vector(),
+            -singlet.into_vector(),
+            epsilon = 1e-6
+        );
     }
-}
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use typenum::consts::U256;
 
     #[test]
-    fn from_classical() {
-        for i in 0..255 {
-            let reg =
-                QuantumRegister::<U256>::from_classical(i.into());
-            assert_eq!(reg.collapse(), i.into())
+    fn apply_if() {
+        use crate::gates::unitary::gates::not;
+        let classical = ClassicalRegister::from(0b10);
+        let mut reg = QuantumRegister::<U4>::basis(0b00);
+        reg.apply_if(&not(), 0, &classical, 0);
+        assert_eq!(reg, QuantumRegister::basis(0b00));
+        reg.apply_if(&not(), 0, &classical, 1);
+        assert_eq!(reg, QuantumRegister::basis(0b10));
+        reg.apply_if(&not(), 1, &classical, 1);
+        assert_eq!(reg, QuantumRegister::basis(0b11));
+    }
+
+    #[test]
+    fn apply_in_place() {
+        use crate::gates::binary::gates::cnot;
+        use crate::gates::unitary::gates::h;
+        let mut reg = QuantumRegister::<U4>::basis(0);
+        reg.apply_unary_in_place(&h(), 0);
+        assert_eq!(
+            reg,
+            QuantumRegister::basis(0).apply_unary(&h(), 0)
+        );
+        reg.apply_in_place(&cnot());
+        assert_relative_eq!(
+            reg.clone().into_vector(),
+            bell_state().into_vector(),
+            epsilon = 1e-6
+        );
+        let expected = cnot()
+            .apply(QuantumRegister::basis(0).apply_unary(&h(), 0));
+        assert_eq!(reg, expected);
+    }
+
+    #[test]
+    fn eq_up_to_phase() {
+        let bell = bell_state();
+        let mut rotated = bell.clone();
+        rotated.qubits *= Complex::exp_ix(1.3);
+        assert!(bell.eq_up_to_phase(&rotated, 1e-6));
+        assert_ne!(bell, rotated);
+        let other =
+            QuantumRegister::<U4>::from_classical(0b01.into());
+        assert!(!bell.eq_up_to_phase(&other, 1e-6));
+        // |1⟩ and -|1⟩ are the same state
+        let one = QuantumRegister::from(Qubit::one());
+        let minus_one = QuantumRegister::from(
+            Qubit::from_amplitudes(Complex::zero(), -Complex::one()),
+        );
+        assert!(one.eq_up_to_phase(&minus_one, 1e-6));
+        assert_ne!(one, minus_one);
+    }
+
+    #[test]
+    fn preparation_circuit() {
+        let zero = || QuantumRegister::<U4>::from_classical(0.into());
+        let bell = bell_state();
+        let circuit = bell.preparation_circuit().unwrap();
+        assert_eq!(circuit.ops().len(), 2);
+        assert!(circuit.run(zero()).eq_up_to_phase(&bell, 1e-5));
+
+        let targets = [
+            QuantumRegister::from_classical(0b11.into()),
+            QuantumRegister::from_2_qubits(
+                Qubit::plus(),
+                Qubit::minus(),
+            ),
+            QuantumRegister::from_2_qubits(
+                Qubit::new(
+                    crate::consts::FRAC_1_SQRT_2.into(),
+                    Complex::i() * crate::consts::FRAC_1_SQRT_2,
+                ),
+                Qubit::one(),
+            ),
+        ];
+        for target in &targets {
+            let circuit = target.preparation_circuit().unwrap();
+            assert!(circuit.run(zero()).eq_up_to_phase(target, 1e-5));
         }
+
+        let magic = QuantumRegister::from_2_qubits(
+            Qubit::from_theta_phi(0.3, 0.2),
+            Qubit::zero(),
+        );
+        assert_eq!(magic.preparation_circuit(), None);
+    }
+
+    #[test]
+    fn separable_across() {
+        let product = QuantumRegister::from_2_qubits(
+            Qubit::plus(),
+            Qubit::from_theta_phi(0.3, 1.2),
+        );
+        assert!(product.is_separable_across(&[0], 1e-6));
+        assert!(product.is_separable_across(&[1], 1e-6));
+        assert!(!bell_state().is_separable_across(&[0], 1e-6));
+
+        let ghz = QuantumRegister::<U8>::ghz();
+        assert!(!ghz.is_separable_across(&[0], 1e-6));
+        assert!(!ghz.is_separable_across(&[1, 2], 1e-6));
+        // A bell pair on qubits 0 and 2, with qubit 1 left alone
+        let mut qubits = VectorN::<Complex, U8>::zeros();
+        qubits[0b000] = crate::consts::FRAC_1_SQRT_2.into();
+        qubits[0b101] = crate::consts::FRAC_1_SQRT_2.into();
+        let reg = QuantumRegister { qubits };
+        assert!(reg.is_separable_across(&[1], 1e-6));
+        assert!(reg.is_separable_across(&[0, 2], 1e-6));
+        assert!(!reg.is_separable_across(&[0, 1], 1e-6));
+    }
+
+    #[test]
+    fn qubit_bloch_product_state() {
+        let (theta, phi) = (1.1, 0.4);
+        let reg = QuantumRegister::from_2_qubits(
+            Qubit::from_theta_phi(theta, phi),
+            Qubit::plus(),
+        );
+        let (x, y, z) = reg.qubit_bloch(0);
+        assert_relative_eq!(
+            x,
+            theta.sin() * phi.cos(),
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            y,
+            theta.sin() * phi.sin(),
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(z, theta.cos(), epsilon = 1e-6);
+        let (x, y, z) = reg.qubit_bloch(1);
+        assert_relative_eq!(x, 1.0, epsilon = 1e-6);
+        assert_relative_eq!(y, 0.0, epsilon = 1e-6);
+        assert_relative_eq!(z, 0.0, epsilon = 1e-6);
     }
 
     #[test]
@@ -140,6 +2064,370 @@ mod tests {
         assert!(count_11 < 575, "Too many |11>");
     }
 
+    #[test]
+    fn display() {
+        assert_eq!(bell_state().to_string(), "0.707|00⟩ + 0.707|11⟩");
+        assert_eq!(
+            format!("{:.1}", bell_state()),
+            "0.7|00⟩ + 0.7|11⟩"
+        );
+        assert_eq!(
+            QuantumRegister::<U8>::basis(0b011).to_string(),
+            "1.000|011⟩"
+        );
+        let reg = QuantumRegister::<U2>::from(Qubit::new(
+            Complex::new(0.6, 0.0),
+            Complex::new(0.0, -0.8),
+        ));
+        assert_eq!(reg.to_string(), "0.600|0⟩ + -0.800i|1⟩");
+        let reg = QuantumRegister::<U2>::from(Qubit::new(
+            Complex::new(0.5, 0.5),
+            Complex::new(0.0, crate::consts::FRAC_1_SQRT_2),
+        ));
+        assert_eq!(format!("{:.1}", reg), "(0.5+0.5i)|0⟩ + 0.7i|1⟩");
+    }
+
+    #[test]
+    fn bell_state_expectations() {
+        use crate::gates::unitary::gates::pauli::{x, y, z};
+        let bell = bell_state();
+        let pair = |a: UnaryGate| a.tensor(&a);
+        assert_relative_eq!(
+            bell.expectation(&pair(z())),
+            1.0,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            bell.expectation(&pair(x())),
+            1.0,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            bell.expectation(&pair(y())),
+            -1.0,
+            epsilon = 1e-6
+        );
+        // Each qubit on its own is maximally mixed
+        let z0 = z().tensor(&UnaryGate::identity());
+        assert_relative_eq!(
+            bell.expectation(&z0),
+            0.0,
+            epsilon = 1e-6
+        );
+        let one_zero = QuantumRegister::<U4>::basis(0b10);
+        assert_relative_eq!(one_zero.expectation(&z0), -1.0);
+    }
+
+    #[test]
+    fn entropy_and_collision_probability() {
+        let basis = QuantumRegister::<U4>::basis(0b01);
+        assert_eq!(basis.shannon_entropy(), 0.0);
+        assert_eq!(basis.collision_probability(), 1.0);
+        let bell = bell_state();
+        assert_relative_eq!(
+            bell.shannon_entropy(),
+            1.0,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            bell.collision_probability(),
+            0.5,
+            epsilon = 1e-6
+        );
+        let w = QuantumRegister::<U8>::w_state();
+        assert_relative_eq!(
+            w.shannon_entropy(),
+            (3 as Float).log2(),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn qft_of_basis_zero_is_uniform() {
+        let zero = QuantumRegister::<U8>::basis(0);
+        let uniform = zero.qft();
+        for p in uniform.probabilities() {
+            assert_relative_eq!(p, 1.0 / 8.0, epsilon = 1e-6);
+        }
+        // Applying the QFT twice reverses the basis states, and fixes 0
+        assert_relative_eq!(
+            uniform.qft().into_vector(),
+            zero.clone().into_vector(),
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            uniform.iqft().into_vector(),
+            zero.into_vector(),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn qft_round_trips() {
+        let reg = QuantumRegister::<U8>::w_state().tensor(
+            &QuantumRegister::<U2>::from(Qubit::from_theta_phi(
+                1.0, 2.0,
+            )),
+        );
+        let there = reg.qft();
+        assert!(QuantumRegister::is_valid(&there.qubits));
+        assert_relative_eq!(
+            there.iqft().into_vector(),
+            reg.into_vector(),
+            epsilon = 1e-5
+        );
+        // A single qubit QFT is the Hadamard gate
+        let q = Qubit::from_theta_phi(0.3, -1.2);
+        assert_relative_eq!(
+            QuantumRegister::<U2>::from(q.clone())
+                .qft()
+                .into_vector(),
+            h().run(q).inner,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn iqft_undoes_qft_on_random_states() {
+        use rand::rngs::SmallRng;
+        use rand::{Rng, SeedableRng};
+        let mut rng = SmallRng::seed_from_u64(21);
+        for _ in 0..10 {
+            let mut reg =
+                QuantumRegister::<U8>::from_vector_unchecked(
+                    VectorN::<Complex, U8>::from_fn(|_, _| {
+                        Complex::new(
+                            rng.gen_range(-1.0, 1.0),
+                            rng.gen_range(-1.0, 1.0),
+                        )
+                    }),
+                );
+            reg.normalize();
+            assert_relative_eq!(
+                reg.qft().iqft().into_vector(),
+                reg.clone().into_vector(),
+                epsilon = 1e-5
+            );
+            assert_relative_eq!(
+                reg.iqft().qft().into_vector(),
+                reg.into_vector(),
+                epsilon = 1e-5
+            );
+        }
+        // Entangled states round trip too
+        let ghz = QuantumRegister::<U8>::ghz();
+        assert_relative_eq!(
+            ghz.qft().iqft().into_vector(),
+            ghz.into_vector(),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn qft_of_basis_state() {
+        // |j⟩ goes to amplitudes e^{2πi jk/N}/√N, all the same size
+        let j = 3;
+        let reg = QuantumRegister::<U8>::basis(j).qft();
+        for (k, amp) in reg.amplitudes().iter().enumerate() {
+            let angle =
+                2.0 * crate::consts::PI * (j * k) as Float / 8.0;
+            assert_relative_eq!(
+                *amp,
+                Complex::exp_ix(angle) * (1.0 / (8 as Float).sqrt()),
+                epsilon = 1e-6
+            );
+        }
+        assert_relative_eq!(
+            reg.iqft().into_vector(),
+            QuantumRegister::<U8>::basis(j).into_vector(),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn diffuse_amplifies_marked_state() {
+        let mut reg = QuantumRegister::<U4>::uniform();
+        reg.diffuse();
+        assert_relative_eq!(
+            reg.clone().into_vector(),
+            QuantumRegister::<U4>::uniform().into_vector(),
+            epsilon = 1e-6
+        );
+        // The oracle flips the phase of |11⟩
+        reg.qubits[0b11] = -reg.qubits[0b11];
+        reg.diffuse();
+        let probs = reg.probabilities();
+        for state in 0..3 {
+            assert!(probs[state] < probs[0b11]);
+        }
+        // With two qubits one round finds it exactly
+        assert_relative_eq!(probs[0b11], 1.0, epsilon = 1e-6);
+        assert!(QuantumRegister::is_valid(&reg.qubits));
+    }
+
+    #[test]
+    fn collapse_in_basis() {
+        let plus_minus = QuantumRegister::<U4>::from_2_qubits(
+            Qubit::plus(),
+            Qubit::minus(),
+        );
+        let plus_i = s().run(Qubit::plus());
+        let minus_i = s().run(Qubit::minus());
+        let y_eigen =
+            QuantumRegister::<U4>::from_2_qubits(plus_i, minus_i);
+        let basis = QuantumRegister::<U4>::basis(0b10);
+        for _ in 0..100 {
+            assert_eq!(
+                plus_minus.collapse_in_basis(Basis::X).bits,
+                0b01
+            );
+            assert_eq!(
+                y_eigen.collapse_in_basis(Basis::Y).bits,
+                0b01
+            );
+            assert_eq!(basis.collapse_in_basis(Basis::Z).bits, 0b10);
+        }
+        // The Bell state is correlated in the X basis as well
+        let bell = bell_state();
+        for _ in 0..100 {
+            let bits = bell.collapse_in_basis(Basis::X).bits;
+            assert!(bits == 0b00 || bits == 0b11);
+        }
+    }
+
+    #[test]
+    fn plus_register_measures_zero_in_x_basis() {
+        let plus = QuantumRegister::<U4>::from_2_qubits(
+            Qubit::plus(),
+            Qubit::plus(),
+        );
+        for _ in 0..100 {
+            assert_eq!(plus.collapse_in_basis(Basis::X).bits, 0);
+        }
+    }
+
+    #[test]
+    fn estimate_probability_within_standard_error() {
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let mut rng = SmallRng::seed_from_u64(21);
+        let bell = bell_state();
+        let (p, err) = bell
+            .estimate_probability_with_rng(0b00, 10_000, &mut rng);
+        assert!((p - 0.5).abs() < 3.0 * err, "{} ± {}", p, err);
+        // 100 times the shots gives a tenth of the error
+        let (_, fine) = bell
+            .estimate_probability_with_rng(0b00, 1_000_000, &mut rng);
+        assert_relative_eq!(fine, err / 10.0, max_relative = 0.05);
+        // Impossible states are certain not to come up
+        assert_eq!(bell.estimate_probability(0b01, 100), (0.0, 0.0));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Can't estimate a probability from 0 shots"
+    )]
+    fn estimate_probability_needs_shots() {
+        bell_state().estimate_probability(0b00, 0);
+    }
+
+    #[test]
+    fn collapse_with_prob() {
+        for i in 0..4 {
+            let (outcome, prob) =
+                QuantumRegister::<U4>::basis(i).collapse_with_prob();
+            assert_eq!(outcome, (i as u64).into());
+            assert_relative_eq!(prob, 1.0);
+        }
+        let bell = QuantumRegister::<U4>::ghz();
+        for _ in 0..100 {
+            let (outcome, prob) = bell.collapse_with_prob();
+            assert!(outcome.bits == 0b00 || outcome.bits == 0b11);
+            assert_relative_eq!(prob, 0.5, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn sample_counts_bell_state_even_dist() {
+        let counts = bell_state().sample_counts(1000);
+        assert_eq!(counts.len(), 4);
+        assert_eq!(counts[0b01], 0);
+        assert_eq!(counts[0b10], 0);
+        assert_eq!(counts.iter().sum::<usize>(), 1000);
+        assert!(425 < counts[0b00], "Too few |00>");
+        assert!(counts[0b00] < 575, "Too many |00>");
+        assert!(425 < counts[0b11], "Too few |11>");
+        assert!(counts[0b11] < 575, "Too many |11>");
+    }
+
+    #[test]
+    fn outcomes_know_their_width() {
+        let outcome =
+            QuantumRegister::<U4>::basis(0b01).collapse_outcome();
+        assert_eq!(outcome.num_qubits, 2);
+        assert_eq!(outcome.bits, 0b01);
+        assert_eq!(outcome.to_string(), "01");
+        assert_eq!(outcome.as_bools(), [false, true]);
+        let mut reg = QuantumRegister::<U8>::ghz();
+        let outcome = reg.measure_outcome();
+        assert_eq!(outcome.num_qubits, 3);
+        assert!(outcome.bits == 0b000 || outcome.bits == 0b111);
+        assert_eq!(
+            reg,
+            QuantumRegister::basis(outcome.bits as usize)
+        );
+    }
+
+    #[test]
+    fn sample_gives_every_shot() {
+        let shots = bell_state().sample(500);
+        assert_eq!(shots.len(), 500);
+        assert!(shots
+            .iter()
+            .all(|c| c.bits == 0b00 || c.bits == 0b11));
+        assert!(shots.iter().any(|c| c.bits == 0b00));
+        assert!(shots.iter().any(|c| c.bits == 0b11));
+        let basis = QuantumRegister::<U8>::basis(0b101).sample(50);
+        assert_eq!(basis, vec![ClassicalRegister::from(0b101); 50]);
+        assert!(bell_state().sample(0).is_empty());
+    }
+
+    #[test]
+    fn seeded_sample_matches_counts() {
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let reg = QuantumRegister::<U8>::w_state();
+        let shots =
+            reg.sample_with_rng(200, &mut SmallRng::seed_from_u64(5));
+        let counts = reg.sample_counts_with_rng(
+            200,
+            &mut SmallRng::seed_from_u64(5),
+        );
+        let mut expected = vec![0; 8];
+        for shot in &shots {
+            expected[shot.bits as usize] += 1;
+        }
+        assert_eq!(counts, expected);
+    }
+
+    #[test]
+    fn seeded_sample_counts_match_collapse() {
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let reg = QuantumRegister::<U8>::w_state();
+        let counts = reg.sample_counts_with_rng(
+            200,
+            &mut SmallRng::seed_from_u64(3),
+        );
+        let mut rng = SmallRng::seed_from_u64(3);
+        let mut expected = vec![0; 8];
+        for _ in 0..200 {
+            expected
+                [reg.collapse_with_rng(&mut rng).bits as usize] += 1;
+        }
+        assert_eq!(counts, expected);
+    }
+
     fn bell_state() -> QuantumRegister<U4> {
         let ket_00 = 0b00;
         let ket_11 = 0b11;
@@ -147,11 +2435,218 @@ mod tests {
             nalgebra::VectorN::<Complex, U4>::from_element(
                 Complex::zero(),
             );
-        qubits[ket_00] = std::f32::consts::FRAC_1_SQRT_2.into();
-        qubits[ket_11] = std::f32::consts::FRAC_1_SQRT_2.into();
+        qubits[ket_00] = crate::consts::FRAC_1_SQRT_2.into();
+        qubits[ket_11] = crate::consts::FRAC_1_SQRT_2.into();
         QuantumRegister { qubits }
     }
 
+    #[test]
+    fn basis() {
+        let reg = QuantumRegister::<U4>::basis(0b11);
+        for _ in 0..100 {
+            assert_eq!(reg.collapse().bits, 0b11);
+        }
+        assert_eq!(
+            QuantumRegister::<U256>::basis(200),
+            QuantumRegister::from_classical(200.into())
+        );
+        for state in 0..8 {
+            let reg = QuantumRegister::<U8>::from(state);
+            assert_eq!(reg, QuantumRegister::basis(state as usize));
+            assert_eq!(reg.collapse().bits, state);
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Basis state 8 out of range for a 8 state register"
+    )]
+    fn from_u64_out_of_range() {
+        let _ = QuantumRegister::<U8>::from(8);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Basis state 4 out of range for a 4 state register"
+    )]
+    fn basis_out_of_range() {
+        QuantumRegister::<U4>::basis(4);
+    }
+
+    #[test]
+    fn tensor() {
+        let zero = QuantumRegister::<U2>::from(Qubit::zero());
+        assert_eq!(
+            zero.tensor(&zero),
+            QuantumRegister::<U4>::basis(0)
+        );
+        let (a, b) =
+            (Qubit::from_theta_phi(0.3, 1.0), Qubit::minus());
+        let ab = QuantumRegister::from(a.clone())
+            .tensor(&QuantumRegister::from(b.clone()));
+        assert_relative_eq!(
+            ab.qubits,
+            QuantumRegister::from_2_qubits(a, b).qubits
+        );
+        let bell_one: QuantumRegister<U8> =
+            bell_state().tensor(&QuantumRegister::from(Qubit::one()));
+        assert_eq!(bell_one.support(1e-6), vec![0b001, 0b111]);
+        assert!(!bell_one.is_separable_across(&[0], 1e-6));
+        assert!(bell_one.is_separable_across(&[2], 1e-6));
+    }
+
+    #[test]
+    fn probabilities() {
+        let bell = bell_state();
+        let probs = bell.probabilities();
+        for (&p, &expected) in probs.iter().zip(&[0.5, 0.0, 0.0, 0.5])
+        {
+            assert_relative_eq!(p, expected, epsilon = 1e-6);
+        }
+        assert_relative_eq!(
+            bell.probability(0b11),
+            0.5,
+            epsilon = 1e-6
+        );
+        assert_eq!(bell.probability(0b01), 0.0);
+        let w = QuantumRegister::<U8>::w_state();
+        assert_relative_eq!(
+            w.probabilities().iter().sum::<Float>(),
+            1.0
+        );
+    }
+
+    #[test]
+    fn from_vector_checks_normalization() {
+        let bell = bell_state().into_vector();
+        assert_eq!(
+            QuantumRegister::try_from_vector(bell),
+            Ok(bell_state())
+        );
+        let doubled = bell * Complex::from_re(2.0);
+        assert_eq!(
+            QuantumRegister::try_from_vector(doubled),
+            Err(RegisterError::NotNormalized)
+        );
+        let unchecked =
+            QuantumRegister::from_vector_unchecked(doubled);
+        assert!(!QuantumRegister::is_valid(&unchecked.qubits));
+    }
+
+    #[test]
+    fn normalize() {
+        let mut reg = QuantumRegister::<U4>::from_vector_unchecked(
+            Vector4::new(
+                Complex::from_re(2.0),
+                Complex::zero(),
+                Complex::zero(),
+                Complex::zero(),
+            ),
+        );
+        reg.normalize();
+        assert_eq!(reg, QuantumRegister::basis(0));
+        assert!(QuantumRegister::is_valid(&reg.qubits));
+
+        let mut bell = bell_state();
+        bell.normalize();
+        assert_relative_eq!(bell.qubits, bell_state().qubits);
+
+        let mut zero = QuantumRegister::<U4>::from_vector_unchecked(
+            Vector4::zeros(),
+        );
+        zero.normalize();
+        assert_eq!(zero.qubits, Vector4::zeros());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid quantum register amplitudes")]
+    fn from_vector_panics_on_unnormalized() {
+        QuantumRegister::from_vector(VectorN::<Complex, U4>::zeros());
+    }
+
+    #[test]
+    fn most_likely() {
+        let (state, prob) =
+            QuantumRegister::<U256>::basis(42).most_likely();
+        assert_eq!(state, 42.into());
+        assert_relative_eq!(prob, 1.0);
+        let reg = QuantumRegister::<U4>::amplitude_encode(&[
+            0.1, 0.2, 0.9, 0.3,
+        ])
+        .unwrap();
+        let (state, prob) = reg.most_likely();
+        assert_eq!(state, 0b10.into());
+        assert_relative_eq!(prob, reg.probability(0b10));
+        // Every state of a uniform register ties
+        let (state, prob) =
+            QuantumRegister::<U8>::uniform().most_likely();
+        assert_eq!(state, 0.into());
+        assert_relative_eq!(prob, 0.125, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn amplitude_of() {
+        let bell = bell_state();
+        let amp = Complex::from_re(crate::consts::FRAC_1_SQRT_2);
+        assert_eq!(bell.amplitude_of("00"), Some(amp));
+        assert_eq!(bell.amplitude_of("11"), Some(amp));
+        assert_eq!(bell.amplitude_of("01"), Some(Complex::zero()));
+        let reg = QuantumRegister::<U8>::basis(0b100);
+        assert_eq!(reg.amplitude_of("100"), Some(Complex::one()));
+        assert_eq!(reg.amplitude_of("001"), Some(Complex::zero()));
+        for label in &["", "0", "000", "0a", "2 ", "１0"] {
+            assert_eq!(bell.amplitude_of(label), None, "{:?}", label);
+        }
+    }
+
+    #[test]
+    fn significant_amplitudes() {
+        let amp = Complex::from_re(crate::consts::FRAC_1_SQRT_2);
+        assert_eq!(
+            bell_state().significant_amplitudes(1e-9),
+            vec![(0b00, amp), (0b11, amp)]
+        );
+        assert!(bell_state().significant_amplitudes(0.6).is_empty());
+        let w = QuantumRegister::<U8>::w_state();
+        let states: Vec<_> = w
+            .significant_amplitudes(0.3)
+            .into_iter()
+            .map(|(state, _)| state)
+            .collect();
+        assert_eq!(states, w.support(1e-6));
+    }
+
+    #[test]
+    fn support() {
+        assert_eq!(bell_state().support(1e-6), vec![0b00, 0b11]);
+        let reg = QuantumRegister::<U256>::from_classical(42.into());
+        assert_eq!(reg.support(1e-6), vec![42]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let reg = bell_state().apply_unary(&s(), 1);
+        let json = serde_json::to_string(&reg).unwrap();
+        let first = format!(
+            r#"[{{"re":{},"im":0.0}},"#,
+            crate::consts::FRAC_1_SQRT_2
+        );
+        assert!(json.starts_with(&first), "{}", json);
+        let back: QuantumRegister<U4> =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(back, reg);
+
+        assert!(serde_json::from_str::<QuantumRegister<U2>>(&json)
+            .is_err());
+        let unnormalized =
+            r#"[{"re":1.0,"im":0.0},{"re":1.0,"im":0.0}]"#;
+        assert!(serde_json::from_str::<QuantumRegister<U2>>(
+            unnormalized
+        )
+        .is_err());
+    }
+
     #[test]
     // Expensive to run
     fn bell_collapse_never_panics_slow() {
@@ -161,6 +2656,75 @@ mod tests {
         }
     }
 
+    /// Probabilities 1/4, 0, 0, 1/4, 0, 0, 1/2, 0
+    fn three_state_register() -> QuantumRegister<U8> {
+        let mut qubits = VectorN::<Complex, U8>::zeros();
+        qubits[0] = Complex::from_re(0.5);
+        qubits[3] = Complex::from_re(-0.5);
+        qubits[6] = Complex::new(0.0, crate::consts::FRAC_1_SQRT_2);
+        QuantumRegister::from_vector(qubits)
+    }
+
+    #[test]
+    fn collapse_with_target_boundaries() {
+        let reg = three_state_register();
+        let at = |target| reg.collapse_with_target(target).bits;
+        assert_eq!(at(0.0), 0);
+        assert_eq!(at(0.2499999), 0);
+        // Boundaries belong to the next state
+        assert_eq!(at(0.25), 3);
+        assert_eq!(at(0.4999999), 3);
+        assert_eq!(at(0.5), 6);
+        assert_eq!(at(0.99999994), 6);
+        // Out of range targets are clamped, rather than wrapped
+        assert_eq!(at(-0.5), 0);
+        assert_eq!(at(1.0), 6);
+        assert_eq!(at(1.3), 6);
+    }
+
+    #[test]
+    fn collapse_with_source() {
+        struct Fixed(Float);
+        impl SampleSource for Fixed {
+            fn next_unit(&mut self) -> Float {
+                self.0
+            }
+        }
+        let reg = three_state_register();
+        for &(target, state) in &[(0.1, 0), (0.3, 3), (0.75, 6)] {
+            let mut source = Fixed(target);
+            assert_eq!(
+                reg.collapse_with_source(&mut source).bits,
+                state
+            );
+        }
+        // Any Rng is a source, giving the same outcomes as collapsing
+        // with it directly
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+        let mut a = SmallRng::seed_from_u64(3);
+        let mut b = SmallRng::seed_from_u64(3);
+        for _ in 0..20 {
+            assert_eq!(
+                reg.collapse_with_source(&mut a),
+                reg.collapse_with_rng(&mut b)
+            );
+        }
+    }
+
+    #[test]
+    fn collapse_with_target_short_total() {
+        // Rounding leaves the total short of 1, so targets near 1 fall
+        // past every slice, and go to the last possible state, not one
+        // of the zeros after it.
+        let mut qubits = VectorN::<Complex, U8>::zeros();
+        qubits[1] = Complex::from_re(0.6);
+        qubits[2] = Complex::from_re(0.79999);
+        let reg = QuantumRegister::from_vector_unchecked(qubits);
+        assert_eq!(reg.collapse_with_target(0.99999994).bits, 2);
+        assert_eq!(reg.collapse_with_target(0.0).bits, 1);
+    }
+
     #[test]
     fn bell_state_colapse_target_approx_1() {
         let target = 0.99999994;
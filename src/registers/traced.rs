@@ -0,0 +1,87 @@
+//! A register that keeps a log of its measurements
+use super::classical::ClassicalRegister;
+use super::quantum::QuantumRegister;
+use crate::complex::Complex;
+
+use nalgebra::allocator::Allocator;
+use nalgebra::default_allocator::DefaultAllocator;
+use nalgebra::dimension::DimName;
+use rand::Rng;
+
+/// A [`QuantumRegister`] that records the outcome of every
+/// [`measure`](Self::measure), in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TracedRegister<N: DimName>
+where
+    DefaultAllocator: Allocator<Complex, N>,
+{
+    reg: QuantumRegister<N>,
+    history: Vec<ClassicalRegister>,
+}
+
+impl<N: DimName> TracedRegister<N>
+where
+    DefaultAllocator: Allocator<Complex, N>,
+{
+    /// Start tracing `reg`, with an empty history
+    pub fn new(reg: QuantumRegister<N>) -> Self {
+        Self {
+            reg,
+            history: Vec::new(),
+        }
+    }
+
+    /// [`QuantumRegister::measure`], logging the outcome
+    pub fn measure(&mut self) -> ClassicalRegister {
+        self.measure_with_rng(&mut rand::thread_rng())
+    }
+
+    /// [`measure`](Self::measure), drawing randomness from `rng`
+    pub fn measure_with_rng<R: Rng>(
+        &mut self,
+        rng: &mut R,
+    ) -> ClassicalRegister {
+        let outcome = self.reg.measure_with_rng(rng);
+        self.history.push(outcome.clone());
+        outcome
+    }
+
+    /// Every outcome so far, oldest first
+    pub fn history(&self) -> &[ClassicalRegister] {
+        &self.history
+    }
+
+    /// The register in its current state
+    pub fn register(&self) -> &QuantumRegister<N> {
+        &self.reg
+    }
+
+    pub fn into_inner(self) -> QuantumRegister<N> {
+        self.reg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::U8;
+
+    #[test]
+    fn repeated_measurements_agree() {
+        let mut traced =
+            TracedRegister::new(QuantumRegister::<U8>::uniform());
+        assert!(traced.history().is_empty());
+        let first = traced.measure();
+        assert_eq!(traced.history().len(), 1);
+        assert_eq!(traced.history()[0], first);
+        for _ in 0..9 {
+            traced.measure();
+        }
+        assert_eq!(traced.history().len(), 10);
+        assert!(traced.history().iter().all(|m| *m == first));
+        assert_eq!(
+            traced.into_inner(),
+            QuantumRegister::basis(first.bits as usize)
+        );
+    }
+}
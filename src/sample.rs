@@ -0,0 +1,19 @@
+//! Where measurements get their randomness from
+
+use crate::Float;
+
+use rand::Rng;
+
+/// A source of the numbers that pick a measurement outcome, each in
+/// `[0, 1)`. Every [`Rng`] is one, drawing uniformly, but a quasi-random
+/// or biased source can be used instead, for variance reduction.
+pub trait SampleSource {
+    /// The next number, in `[0, 1)`
+    fn next_unit(&mut self) -> Float;
+}
+
+impl<R: Rng + ?Sized> SampleSource for R {
+    fn next_unit(&mut self) -> Float {
+        self.gen()
+    }
+}
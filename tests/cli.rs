@@ -0,0 +1,96 @@
+//! The command line parser builds the same circuits as the builder API.
+#![cfg(feature = "cli")]
+
+use clap::error::ErrorKind;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use toy_quant::circuit::Circuit;
+use toy_quant::cli::{parse_args, sample_counts, CliError};
+
+#[test]
+fn parses_bell_circuit() {
+    let args = parse_args(vec![
+        "--qubits", "2", "--gate", "h:0", "--gate", "cnot:0,1",
+        "--shots", "500",
+    ])
+    .unwrap();
+    assert_eq!(args.circuit, Circuit::new(2).h(0).cnot(0, 1));
+    assert_eq!(args.shots, 500);
+
+    let mut rng = StdRng::seed_from_u64(3);
+    let counts = sample_counts(&args.circuit, args.shots, &mut rng);
+    assert_eq!(counts.iter().sum::<usize>(), 500);
+    assert_eq!(counts[0b01] + counts[0b10], 0);
+    assert!(counts[0b00] > 200 && counts[0b11] > 200);
+}
+
+#[test]
+fn samples_noise_on_every_shot() {
+    use toy_quant::noise::depolarizing;
+    let mut rng = StdRng::seed_from_u64(8);
+    let noiseless = Circuit::new(1).x(0);
+    assert_eq!(sample_counts(&noiseless, 200, &mut rng), [0, 200]);
+    // Each run ends in a basis state, so only fresh noise for each shot
+    // can give both outcomes
+    let noisy = noiseless.with_noise(depolarizing(1.0));
+    let counts = sample_counts(&noisy, 200, &mut rng);
+    assert_eq!(counts.iter().sum::<usize>(), 200);
+    assert!(counts[0] > 50 && counts[1] > 50, "{:?}", counts);
+}
+
+#[test]
+fn rejects_bad_arguments() {
+    let parse = |args: &[&str]| parse_args(args.iter().copied());
+    let usage = |args: &[&str]| match parse(args) {
+        Err(CliError::Usage { kind, .. }) => kind,
+        other => panic!("{:?} parsed as {:?}", args, other),
+    };
+    assert_eq!(
+        usage(&["--gate", "h:0"]),
+        ErrorKind::MissingRequiredArgument
+    );
+    assert_eq!(
+        usage(&["--qubits", "2", "--frob"]),
+        ErrorKind::UnknownArgument
+    );
+    assert_eq!(usage(&["--qubits"]), ErrorKind::InvalidValue);
+    assert_eq!(
+        usage(&["--qubits", "2", "--shots", "many"]),
+        ErrorKind::ValueValidation
+    );
+    assert_eq!(
+        parse(&["--qubits", "0"]),
+        Err(CliError::BadQubitCount(0))
+    );
+    assert_eq!(
+        parse(&["--qubits", "2", "--shots", "0"]),
+        Err(CliError::NoShots)
+    );
+    for spec in
+        &["h", "h:2", "h:x", "foo:0", "cnot:0", "cnot:1,1", "x:0,1"]
+    {
+        match parse(&["--qubits", "2", "--gate", spec]) {
+            Err(CliError::BadGate { spec: got, .. }) => {
+                assert_eq!(&got, spec)
+            }
+            other => panic!("{} parsed as {:?}", spec, other),
+        }
+    }
+    let err = parse(&["--qubits", "2", "--gate", "h:2"]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "bad gate \"h:2\": qubit 2 out of range for a 2 qubit circuit"
+    );
+    // The same gate names and checks as JSON circuits
+    let err = parse(&["--qubits", "2", "--gate", "u:0"]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "bad gate \"u:0\": unknown gate \"u\""
+    );
+    let err =
+        parse(&["--qubits", "2", "--gate", "cnot:1,1"]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "bad gate \"cnot:1,1\": cnot needs two different qubits"
+    );
+}
@@ -0,0 +1,68 @@
+//! Every random operation can be driven by one seeded RNG, so whole
+//! simulations are reproducible.
+
+use std::collections::HashSet;
+
+use nalgebra::{U4, U8};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use toy_quant::circuit::Circuit;
+use toy_quant::gates::binary::gates::cnot;
+use toy_quant::gates::unitary::gates::{h, pauli};
+use toy_quant::noise::amplitude_damping;
+use toy_quant::qubit::Qubit;
+use toy_quant::registers::quantum::QuantumRegister;
+
+/// Run a small simulation, logging every measurement it makes
+fn simulate(seed: u64) -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut log = Vec::new();
+    let qubit = Qubit::from_theta_phi(1.2, 0.5);
+    let ghz = QuantumRegister::<U8>::ghz();
+    let bell = cnot().apply(QuantumRegister::from_2_qubits(
+        h().run(Qubit::zero()),
+        Qubit::zero(),
+    ));
+    let damping = amplitude_damping(0.3);
+    let noisy =
+        Circuit::new(2).h(0).cnot(0, 1).with_noise(damping.clone());
+    for _ in 0..200 {
+        log.push(format!(
+            "qubit {}",
+            qubit.sample_with_rng(&mut rng)
+        ));
+        log.push(format!(
+            "ghz {}",
+            ghz.collapse_with_rng(&mut rng).bits
+        ));
+        log.push(format!(
+            "bell {}",
+            bell.collapse_with_rng(&mut rng).bits
+        ));
+        let out = noisy
+            .run_with_rng(QuantumRegister::<U4>::basis(0), &mut rng);
+        log.push(format!("noisy {:?}", out.amplitudes()));
+        let mut damped = Qubit::one();
+        damped.apply_kraus(damping.kraus(), &mut rng);
+        log.push(format!("damped {:?}", damped.bloch()));
+    }
+    let estimate =
+        qubit.sampled_expectation(&pauli::x(), 1000, &mut rng);
+    log.push(format!("<X> ~ {}", estimate));
+    log
+}
+
+#[test]
+fn equal_seeds_give_equal_logs() {
+    assert_eq!(simulate(7), simulate(7));
+    assert_ne!(simulate(7), simulate(8));
+    // The noise really is random, so the logs above cover it
+    let log = simulate(7);
+    for kind in &["noisy", "damped"] {
+        let outcomes: HashSet<_> = log
+            .iter()
+            .filter(|line| line.starts_with(kind))
+            .collect();
+        assert!(outcomes.len() > 1, "{} never varied", kind);
+    }
+}
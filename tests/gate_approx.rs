@@ -0,0 +1,33 @@
+//! Gates can be compared approximately from outside the crate.
+
+use approx::{assert_relative_eq, relative_eq};
+use toy_quant::gates::binary::{gates as binary, BinaryGate};
+use toy_quant::gates::unitary::{gates, UnaryGate};
+
+#[test]
+fn unary_gates() {
+    let h = gates::h();
+    assert_relative_eq!(
+        h.compose(&h),
+        UnaryGate::identity(),
+        epsilon = 1e-6
+    );
+    assert_relative_eq!(
+        gates::s().compose(&gates::s()),
+        gates::z(),
+        epsilon = 1e-6
+    );
+    assert!(!relative_eq!(h, gates::not(), epsilon = 1e-6));
+}
+
+#[test]
+fn binary_gates() {
+    let cnot = binary::cnot();
+    assert_relative_eq!(cnot.compose(&cnot), BinaryGate::identity());
+    assert_relative_eq!(
+        binary::sqrt_swap().pow(2),
+        binary::swap(),
+        epsilon = 1e-6
+    );
+    assert!(!relative_eq!(cnot, binary::cz(), epsilon = 1e-6));
+}
@@ -0,0 +1,31 @@
+//! With the `double-precision` feature, deep circuits stay normalized
+//! far more tightly than the `1e-6` tolerance `f32` needs.
+#![cfg(feature = "double-precision")]
+
+use nalgebra::U8;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use toy_quant::circuit::Circuit;
+use toy_quant::gates::unitary::UnaryGate;
+use toy_quant::registers::quantum::QuantumRegister;
+
+#[test]
+fn random_circuit_stays_normalized() {
+    let mut rng = StdRng::seed_from_u64(20);
+    let mut circuit = Circuit::new(3);
+    for i in 0..20 {
+        let target = rng.gen_range(0, 3);
+        circuit = if i % 2 == 0 {
+            circuit.unary(UnaryGate::random(&mut rng), target)
+        } else {
+            circuit.cnot(target, (target + 1) % 3)
+        };
+    }
+    let out = circuit.run(QuantumRegister::<U8>::basis(0));
+    let total = out.total_probability();
+    assert!(
+        (total - 1.0).abs() < 1e-12,
+        "total probability {}",
+        total
+    );
+}
@@ -0,0 +1,42 @@
+//! Teleporting a qubit with two classical bits and a Bell pair, using
+//! mid-circuit measurement.
+
+use approx::assert_relative_eq;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use toy_quant::protocols::teleport_with_rng;
+use toy_quant::qubit::Qubit;
+
+#[test]
+fn teleports_random_qubits() {
+    let mut rng = StdRng::seed_from_u64(4);
+    let mut outcomes = [false; 4];
+    for _ in 0..200 {
+        let input = Qubit::random(&mut rng);
+        let (z_bit, x_bit, output) =
+            teleport_with_rng(&input, &mut rng);
+        outcomes[2 * z_bit as usize + x_bit as usize] = true;
+        assert_relative_eq!(
+            output,
+            input.canonical(),
+            epsilon = 1e-5
+        );
+    }
+    // Every correction gets exercised
+    assert_eq!(outcomes, [true; 4]);
+}
+
+#[test]
+fn teleports_basis_states() {
+    let mut rng = StdRng::seed_from_u64(9);
+    for input in &[Qubit::zero(), Qubit::one(), Qubit::plus()] {
+        for _ in 0..20 {
+            let (_, _, output) = teleport_with_rng(input, &mut rng);
+            assert_relative_eq!(
+                output.fidelity(input),
+                1.0,
+                epsilon = 1e-5
+            );
+        }
+    }
+}